@@ -0,0 +1,4 @@
+// Rebuild the crate when the embedded database migrations change
+fn main() {
+    println!("cargo:rerun-if-changed=migration");
+}
@@ -0,0 +1,49 @@
+CREATE SCHEMA IF NOT EXISTS github;
+CREATE TABLE IF NOT EXISTS github.repository (
+    id SERIAL PRIMARY KEY,
+    repository_name TEXT NOT NULL,
+    organization_name TEXT NOT NULL,
+    total_stars INT NOT NULL,
+    UNIQUE (repository_name, organization_name)
+);
+CREATE TABLE IF NOT EXISTS github.crawl_checkpoint (
+    crawl_id TEXT PRIMARY KEY,
+    snapshot JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS github.repository_stars_snapshot (
+    repository_id INT NOT NULL REFERENCES github.repository (id) ON DELETE CASCADE,
+    crawled_at TIMESTAMPTZ NOT NULL,
+    observed_at TIMESTAMPTZ NOT NULL,
+    total_stars INT NOT NULL,
+    PRIMARY KEY (repository_id, crawled_at)
+);
+
+ALTER TABLE github.repository
+    ADD COLUMN IF NOT EXISTS database_id BIGINT,
+    ADD COLUMN IF NOT EXISTS node_id TEXT,
+    ADD COLUMN IF NOT EXISTS primary_language TEXT,
+    ADD COLUMN IF NOT EXISTS fork_count INT,
+    ADD COLUMN IF NOT EXISTS is_fork BOOLEAN,
+    ADD COLUMN IF NOT EXISTS is_archived BOOLEAN,
+    ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS pushed_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS license TEXT,
+    ADD COLUMN IF NOT EXISTS description TEXT,
+    ADD COLUMN IF NOT EXISTS topics TEXT[];
+
+ALTER TABLE github.repository
+    DROP CONSTRAINT IF EXISTS repository_repository_name_organization_name_key;
+CREATE UNIQUE INDEX IF NOT EXISTS repository_node_id_key ON github.repository (node_id);
+CREATE INDEX IF NOT EXISTS repository_name_idx ON github.repository (organization_name, repository_name);
+
+CREATE TABLE IF NOT EXISTS github.repository_rename (
+    id SERIAL PRIMARY KEY,
+    repository_id INT NOT NULL REFERENCES github.repository (id) ON DELETE CASCADE,
+    previous_repository_name TEXT NOT NULL,
+    previous_organization_name TEXT NOT NULL,
+    repository_name TEXT NOT NULL,
+    organization_name TEXT NOT NULL,
+    renamed_at TIMESTAMPTZ NOT NULL
+);
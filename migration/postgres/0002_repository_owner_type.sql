@@ -0,0 +1,2 @@
+ALTER TABLE github.repository
+    ADD COLUMN IF NOT EXISTS owner_type TEXT;
@@ -0,0 +1,36 @@
+CREATE TABLE IF NOT EXISTS repository (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    node_id TEXT NOT NULL UNIQUE,
+    repository_name TEXT NOT NULL,
+    organization_name TEXT NOT NULL,
+    total_stars INTEGER NOT NULL,
+    database_id INTEGER,
+    primary_language TEXT,
+    fork_count INTEGER,
+    is_fork BOOLEAN,
+    is_archived BOOLEAN,
+    created_at TEXT,
+    pushed_at TEXT,
+    license TEXT,
+    description TEXT,
+    topics TEXT
+);
+CREATE INDEX IF NOT EXISTS repository_name_idx ON repository (organization_name, repository_name);
+
+CREATE TABLE IF NOT EXISTS repository_stars_snapshot (
+    repository_id INTEGER NOT NULL REFERENCES repository (id) ON DELETE CASCADE,
+    crawled_at TEXT NOT NULL,
+    observed_at TEXT NOT NULL,
+    total_stars INTEGER NOT NULL,
+    PRIMARY KEY (repository_id, crawled_at)
+);
+
+CREATE TABLE IF NOT EXISTS repository_rename (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    repository_id INTEGER NOT NULL REFERENCES repository (id) ON DELETE CASCADE,
+    previous_repository_name TEXT NOT NULL,
+    previous_organization_name TEXT NOT NULL,
+    repository_name TEXT NOT NULL,
+    organization_name TEXT NOT NULL,
+    renamed_at TEXT NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE repository ADD COLUMN owner_type TEXT;
@@ -0,0 +1 @@
+ALTER TABLE repository ADD COLUMN unreachable_at TEXT;
@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use log::info;
+
+use crate::{CrawlerStateCheckpointer, CrawlerStateSnapshot, StdResult};
+
+/// A checkpointer that stores snapshots of the crawler state as JSON files in a local directory.
+pub struct FileCheckpointer {
+    /// The directory where the snapshots are stored.
+    directory: PathBuf,
+}
+
+impl FileCheckpointer {
+    /// Creates a new `FileCheckpointer` instance with the given directory.
+    pub fn new(directory: &Path) -> Self {
+        Self {
+            directory: directory.to_path_buf(),
+        }
+    }
+
+    fn snapshot_path(&self, crawl_id: &str) -> PathBuf {
+        self.directory.join(format!("{crawl_id}.json"))
+    }
+}
+
+#[async_trait::async_trait]
+impl CrawlerStateCheckpointer for FileCheckpointer {
+    async fn save(&self, crawl_id: &str, snapshot: &CrawlerStateSnapshot) -> StdResult<()> {
+        tokio::fs::create_dir_all(&self.directory)
+            .await
+            .with_context(|| format!("Failed to create directory {:?}", self.directory))?;
+        let snapshot_path = self.snapshot_path(crawl_id);
+        let temporary_path = snapshot_path.with_extension("json.tmp");
+        tokio::fs::write(&temporary_path, serde_json::to_vec(snapshot)?).await?;
+        tokio::fs::rename(&temporary_path, &snapshot_path).await?;
+        info!("Saved checkpoint to {snapshot_path:?}");
+
+        Ok(())
+    }
+
+    async fn load(&self, crawl_id: &str) -> StdResult<Option<CrawlerStateSnapshot>> {
+        let snapshot_path = self.snapshot_path(crawl_id);
+        if !tokio::fs::try_exists(&snapshot_path).await? {
+            return Ok(None);
+        }
+        let snapshot = tokio::fs::read(&snapshot_path).await?;
+
+        Ok(Some(serde_json::from_slice(&snapshot).with_context(
+            || format!("Failed to parse checkpoint {snapshot_path:?}"),
+        )?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Request;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn load_returns_none_when_no_checkpoint_saved() {
+        let directory = tempfile::tempdir().unwrap();
+        let checkpointer = FileCheckpointer::new(&directory.path().join("checkpoints"));
+
+        let snapshot = checkpointer.load("crawl-1").await.unwrap();
+
+        assert_eq!(None, snapshot);
+    }
+
+    #[tokio::test]
+    async fn save_and_load_checkpoint() {
+        let directory = tempfile::tempdir().unwrap();
+        let checkpointer = FileCheckpointer::new(&directory.path().join("checkpoints"));
+        let snapshot = CrawlerStateSnapshot {
+            requests_queued: vec![Request::dummy_search_organization()],
+            requests_pushed: vec![Request::dummy_search_organization()],
+            total_repositories_target: 10,
+            total_persisted_repositories: 5,
+            ..CrawlerStateSnapshot::default()
+        };
+
+        checkpointer.save("crawl-1", &snapshot).await.unwrap();
+        let loaded_snapshot = checkpointer.load("crawl-1").await.unwrap();
+
+        assert_eq!(Some(snapshot), loaded_snapshot);
+    }
+}
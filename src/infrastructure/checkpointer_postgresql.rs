@@ -0,0 +1,56 @@
+use sqlx::{PgPool, postgres::PgPoolOptions};
+
+use crate::{CrawlerStateCheckpointer, CrawlerStateSnapshot, StdResult};
+
+const SAVE_QUERY: &str = r#"
+INSERT INTO github.crawl_checkpoint (crawl_id, snapshot, updated_at)
+VALUES ($1, $2::jsonb, NOW())
+ON CONFLICT (crawl_id) DO UPDATE
+SET snapshot = EXCLUDED.snapshot, updated_at = EXCLUDED.updated_at;
+"#;
+
+const LOAD_QUERY: &str = r#"
+SELECT snapshot::text
+FROM github.crawl_checkpoint
+WHERE crawl_id = $1;
+"#;
+
+/// A checkpointer that stores snapshots of the crawler state in a PostgreSQL database.
+pub struct PostgresSqlCheckpointer {
+    pool: PgPool,
+}
+
+impl PostgresSqlCheckpointer {
+    /// Creates a new `PostgresSqlCheckpointer` instance.
+    pub async fn try_new(connection_string: &str) -> StdResult<Self> {
+        Ok(Self {
+            pool: PgPoolOptions::new()
+                .max_connections(1)
+                .connect(connection_string)
+                .await?,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl CrawlerStateCheckpointer for PostgresSqlCheckpointer {
+    async fn save(&self, crawl_id: &str, snapshot: &CrawlerStateSnapshot) -> StdResult<()> {
+        sqlx::query(SAVE_QUERY)
+            .bind(crawl_id)
+            .bind(serde_json::to_string(snapshot)?)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn load(&self, crawl_id: &str) -> StdResult<Option<CrawlerStateSnapshot>> {
+        let row: Option<(String,)> = sqlx::query_as(LOAD_QUERY)
+            .bind(crawl_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|(snapshot,)| serde_json::from_str(&snapshot).map_err(|e| e.into()))
+            .transpose()
+    }
+}
@@ -0,0 +1,236 @@
+use std::{sync::Arc, time::Duration};
+
+use log::{error, warn};
+use tokio::time::interval;
+
+use crate::{CrawlerState, CrawlerStateCheckpointer, RepositoryCrawler, Request, StdResult};
+
+/// A crawler that periodically saves a checkpoint of the crawler state while another crawler is running.
+pub struct CheckpointCrawler {
+    /// The crawler to be checkpointed
+    crawler: Arc<dyn RepositoryCrawler>,
+
+    /// The checkpointer used to save the snapshots
+    checkpointer: Arc<dyn CrawlerStateCheckpointer>,
+
+    /// The identifier of the crawl
+    crawl_id: String,
+
+    /// The delay between two checkpoints
+    checkpoint_interval: Duration,
+
+    /// The state of the crawler
+    state: Arc<CrawlerState>,
+}
+
+impl CheckpointCrawler {
+    /// Creates a new `CheckpointCrawler` instance.
+    pub fn new(
+        crawler: Arc<dyn RepositoryCrawler>,
+        checkpointer: Arc<dyn CrawlerStateCheckpointer>,
+        crawl_id: &str,
+        checkpoint_interval: Duration,
+        state: Arc<CrawlerState>,
+    ) -> Self {
+        Self {
+            crawler,
+            checkpointer,
+            crawl_id: crawl_id.to_string(),
+            checkpoint_interval,
+            state,
+        }
+    }
+
+    /// Saves a checkpoint of the state once the repositories of the requests it acknowledges are flushed.
+    ///
+    /// The snapshot is taken before the flush, so that it only acknowledges requests whose repositories are covered by the
+    /// flush, and the checkpoint is abandoned if the flush fails.
+    async fn save_checkpoint(&self) {
+        let snapshot = self.state.snapshot().await;
+        if let Err(e) = self.crawler.flush().await {
+            error!(
+                "Abandoned checkpoint for crawl {} after failing to flush the persister: {e}",
+                self.crawl_id
+            );
+            return;
+        }
+        match self.checkpointer.save(&self.crawl_id, &snapshot).await {
+            Ok(()) => warn!(
+                "Saved checkpoint for crawl {}: queued={} in_progress={}",
+                self.crawl_id,
+                snapshot.requests_queued.len(),
+                snapshot.requests_in_progress.len()
+            ),
+            Err(e) => error!("Failed to save checkpoint for crawl {}: {e}", self.crawl_id),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RepositoryCrawler for CheckpointCrawler {
+    async fn crawl(&self, requests: Vec<Request>, total_repositories: u32) -> StdResult<()> {
+        let crawl = self.crawler.crawl(requests, total_repositories);
+        tokio::pin!(crawl);
+        let mut checkpoint_interval = interval(self.checkpoint_interval);
+        checkpoint_interval.tick().await;
+        let result = loop {
+            tokio::select! {
+                result = &mut crawl => break result,
+                _ = checkpoint_interval.tick() => self.save_checkpoint().await,
+            }
+        };
+        self.save_checkpoint().await;
+
+        result
+    }
+
+    async fn flush(&self) -> StdResult<()> {
+        self.crawler.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+    use tokio::time::sleep;
+
+    use crate::{MockCrawlerStateCheckpointer, MockRepositoryCrawler};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn crawl_saves_final_checkpoint() {
+        let crawler = {
+            let mut crawler = MockRepositoryCrawler::new();
+            crawler.expect_crawl().returning(|_, _| Ok(())).times(1);
+            crawler.expect_flush().returning(|| Ok(())).times(1);
+
+            crawler
+        };
+        let checkpointer = {
+            let mut checkpointer = MockCrawlerStateCheckpointer::new();
+            checkpointer
+                .expect_save()
+                .withf(|crawl_id, _| crawl_id == "crawl-1")
+                .returning(|_, _| Ok(()))
+                .times(1);
+
+            checkpointer
+        };
+        let crawler = CheckpointCrawler::new(
+            Arc::new(crawler),
+            Arc::new(checkpointer),
+            "crawl-1",
+            Duration::from_secs(60),
+            Arc::new(CrawlerState::default()),
+        );
+
+        crawler
+            .crawl(vec![Request::dummy_search_organization()], 10)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn crawl_saves_periodic_checkpoints() {
+        struct SlowCrawler;
+
+        #[async_trait::async_trait]
+        impl RepositoryCrawler for SlowCrawler {
+            async fn crawl(&self, _requests: Vec<Request>, _total: u32) -> StdResult<()> {
+                sleep(Duration::from_millis(250)).await;
+
+                Ok(())
+            }
+        }
+
+        let checkpointer = {
+            let mut checkpointer = MockCrawlerStateCheckpointer::new();
+            checkpointer
+                .expect_save()
+                .returning(|_, _| Ok(()))
+                .times(3..);
+
+            checkpointer
+        };
+        let crawler = CheckpointCrawler::new(
+            Arc::new(SlowCrawler),
+            Arc::new(checkpointer),
+            "crawl-1",
+            Duration::from_millis(100),
+            Arc::new(CrawlerState::default()),
+        );
+
+        crawler
+            .crawl(vec![Request::dummy_search_organization()], 10)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn crawl_saves_final_checkpoint_when_crawler_fails() {
+        let crawler = {
+            let mut crawler = MockRepositoryCrawler::new();
+            crawler
+                .expect_crawl()
+                .returning(|_, _| Err(anyhow!("Crawler failed")))
+                .times(1);
+            crawler.expect_flush().returning(|| Ok(())).times(1);
+
+            crawler
+        };
+        let checkpointer = {
+            let mut checkpointer = MockCrawlerStateCheckpointer::new();
+            checkpointer
+                .expect_save()
+                .returning(|_, _| Err(anyhow!("Checkpoint failed")))
+                .times(1);
+
+            checkpointer
+        };
+        let crawler = CheckpointCrawler::new(
+            Arc::new(crawler),
+            Arc::new(checkpointer),
+            "crawl-1",
+            Duration::from_secs(60),
+            Arc::new(CrawlerState::default()),
+        );
+
+        crawler
+            .crawl(vec![Request::dummy_search_organization()], 10)
+            .await
+            .expect_err("Crawler should fail if the checkpointed crawler fails");
+    }
+
+    #[tokio::test]
+    async fn crawl_abandons_checkpoint_when_flush_fails() {
+        let crawler = {
+            let mut crawler = MockRepositoryCrawler::new();
+            crawler.expect_crawl().returning(|_, _| Ok(())).times(1);
+            crawler
+                .expect_flush()
+                .returning(|| Err(anyhow!("Flush failed")))
+                .times(1);
+
+            crawler
+        };
+        let checkpointer = {
+            let mut checkpointer = MockCrawlerStateCheckpointer::new();
+            checkpointer.expect_save().never();
+
+            checkpointer
+        };
+        let crawler = CheckpointCrawler::new(
+            Arc::new(crawler),
+            Arc::new(checkpointer),
+            "crawl-1",
+            Duration::from_secs(60),
+            Arc::new(CrawlerState::default()),
+        );
+
+        crawler
+            .crawl(vec![Request::dummy_search_organization()], 10)
+            .await
+            .unwrap();
+    }
+}
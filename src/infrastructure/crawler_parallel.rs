@@ -2,10 +2,13 @@ use std::{sync::Arc, time::Duration};
 
 use anyhow::anyhow;
 use log::warn;
-use tokio::time::sleep;
+use tokio::{task::JoinHandle, time::sleep};
 
 use crate::{CrawlerState, RepositoryCrawler, Request, StdResult};
 
+/// The default delay given to the crawlers to finish their requests in progress on shutdown.
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// A parallel crawler that uses multiple crawlers to fetch repositories concurrently.
 pub struct ParallelCrawler {
     /// The worker crawlers
@@ -16,6 +19,9 @@ pub struct ParallelCrawler {
 
     /// The state of the crawler
     state: Arc<CrawlerState>,
+
+    /// The delay given to the crawlers to finish their requests in progress on shutdown
+    shutdown_timeout: Duration,
 }
 
 impl ParallelCrawler {
@@ -29,8 +35,32 @@ impl ParallelCrawler {
             crawlers,
             delay_between_crawlers,
             state,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
         }
     }
+
+    /// Sets the delay given to the crawlers to finish their requests in progress on shutdown.
+    pub fn with_shutdown_timeout(mut self, shutdown_timeout: Duration) -> Self {
+        self.shutdown_timeout = shutdown_timeout;
+        self
+    }
+
+    async fn wait_for_crawlers(handles: Vec<JoinHandle<StdResult<()>>>) -> StdResult<()> {
+        for handle in handles {
+            handle.await??;
+        }
+
+        Ok(())
+    }
+
+    async fn wait_for_shutdown_timeout(&self) {
+        self.state.wait_for_shutdown().await;
+        warn!(
+            "Shutdown requested, waiting up to {:?} for the requests in progress",
+            self.shutdown_timeout
+        );
+        sleep(self.shutdown_timeout).await;
+    }
 }
 
 #[async_trait::async_trait]
@@ -50,6 +80,9 @@ impl RepositoryCrawler for ParallelCrawler {
             if !handles.is_empty() {
                 sleep(self.delay_between_crawlers).await;
             }
+            if self.state.is_shutdown_requested().await {
+                break;
+            }
 
             let crawler_clone = Arc::clone(crawler);
             let handle =
@@ -58,11 +91,41 @@ impl RepositoryCrawler for ParallelCrawler {
             warn!("Started crawler {}/{}", handles.len(), self.crawlers.len());
         }
 
-        for handle in handles {
-            handle.await??;
+        let abort_handles = handles
+            .iter()
+            .map(|handle| handle.abort_handle())
+            .collect::<Vec<_>>();
+        let result = tokio::select! {
+            result = Self::wait_for_crawlers(handles) => result,
+            _ = self.wait_for_shutdown_timeout() => {
+                warn!("Shutdown timeout exceeded, aborting the crawlers");
+                for abort_handle in abort_handles {
+                    abort_handle.abort();
+                }
+
+                // The aborted crawlers did not flush the repositories of the requests they acknowledged
+                self.flush().await
+            }
+        };
+        let total_requeued_requests = self.state.requeue_in_progress_requests().await;
+        if total_requeued_requests > 0 {
+            warn!("Requeued {total_requeued_requests} requests in progress");
         }
 
-        Ok(())
+        result
+    }
+
+    /// Flushes each crawler, returning the first error once all of them are flushed.
+    async fn flush(&self) -> StdResult<()> {
+        let mut result = Ok(());
+        for crawler in &self.crawlers {
+            if let Err(e) = crawler.flush().await {
+                warn!("Failed to flush a crawler: {e}");
+                result = result.and(Err(e));
+            }
+        }
+
+        result
     }
 }
 
@@ -72,7 +135,10 @@ mod tests {
 
     use super::*;
     use crate::{MockRepositoryCrawler, Request};
-    use std::sync::Arc;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    };
 
     #[tokio::test]
     async fn crawl_with_no_requests() {
@@ -175,6 +241,57 @@ mod tests {
             .expect_err("Crawler should fail if one crawler fails");
     }
 
+    #[tokio::test]
+    async fn crawl_aborts_crawlers_and_requeues_requests_after_shutdown_timeout() {
+        struct StuckCrawler {
+            state: Arc<CrawlerState>,
+            flushed: AtomicBool,
+        }
+
+        #[async_trait::async_trait]
+        impl RepositoryCrawler for StuckCrawler {
+            async fn crawl(&self, _requests: Vec<Request>, _total: u32) -> StdResult<()> {
+                self.state.pop_request().await;
+                self.state.request_shutdown().await;
+                sleep(Duration::from_secs(3600)).await;
+
+                Ok(())
+            }
+
+            async fn flush(&self) -> StdResult<()> {
+                self.flushed.store(true, Ordering::SeqCst);
+
+                Ok(())
+            }
+        }
+
+        let state = Arc::new(CrawlerState::default());
+        let stuck_crawler = Arc::new(StuckCrawler {
+            state: state.clone(),
+            flushed: AtomicBool::new(false),
+        });
+        let crawler = ParallelCrawler::new(
+            vec![stuck_crawler.clone()],
+            Duration::from_secs(0),
+            state.clone(),
+        )
+        .with_shutdown_timeout(Duration::from_millis(10));
+
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            crawler.crawl(vec![Request::dummy_search_organization()], 10),
+        )
+        .await
+        .expect("Crawler should stop after the shutdown timeout")
+        .unwrap();
+
+        assert!(stuck_crawler.flushed.load(Ordering::SeqCst));
+        assert_eq!(
+            Some(Request::dummy_search_organization()),
+            state.pop_request().await
+        );
+    }
+
     #[tokio::test]
     async fn crawl_starts_crawler_with_expected_delay() {
         let now = Utc::now();
@@ -1,10 +1,10 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc, time::Instant};
 
 use log::{info, warn};
 
 use crate::{
-    CrawlerState, RepositoryCrawler, RepositoryFetcher, RepositoryPersister, Request, Response,
-    StdResult,
+    CrawlerState, FetcherError, RepositoryCrawler, RepositoryFetcher, RepositoryPersister, Request,
+    Response, StdResult,
 };
 
 /// A worker crawler
@@ -17,6 +17,9 @@ pub struct WorkerCrawler {
 
     /// The state of the crawler
     state: Arc<CrawlerState>,
+
+    /// The maximum number of organization repositories requests fetched in a single batch
+    batch_size: usize,
 }
 
 impl WorkerCrawler {
@@ -30,12 +33,116 @@ impl WorkerCrawler {
             fetcher,
             persister,
             state,
+            batch_size: 1,
+        }
+    }
+
+    /// Sets the maximum number of organization repositories requests fetched in a single batch.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Fetches a request and processes its response, queuing the next requests.
+    async fn process_request(&self, request: &Request) -> StdResult<()> {
+        self.state.increment_total_fetcher_calls(1).await;
+        let started_at = Instant::now();
+        let fetched = self.fetcher.fetch(request).await;
+        self.state
+            .observe_request_latency(request.kind(), started_at.elapsed())
+            .await;
+        if let Some((response, next_requests)) = fetched? {
+            self.process_response(&response, request).await?;
+            self.attribute_seed_query(request, &response, &next_requests)
+                .await;
+            self.state.push_requests(next_requests).await;
+        }
+        if let Request::RefreshRepositories(request) = request {
+            self.state
+                .increment_total_refreshed_repositories(request.node_ids.len() as u32)
+                .await;
         }
+
+        Ok(())
+    }
+
+    /// Processes the queued requests until the crawl completes or a shutdown is requested.
+    async fn process_requests(&self) -> StdResult<()> {
+        while !self.state.is_shutdown_requested().await && !self.state.has_completed().await? {
+            if let Some(request) = self.state.pop_request_batch(self.batch_size).await {
+                info!("Processing request: {request}");
+                self.try_process_request(&request).await?;
+                self.state.acknowledge_request(&request).await;
+                warn!("{}", self.state.state_summary().await);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Processes a request, skipping it if it fails with an error of its own.
+    ///
+    /// A failed batch is split, so that each of its requests is processed and skipped on its own.
+    async fn try_process_request(&self, request: &Request) -> StdResult<()> {
+        let Err(e) = self.process_request(request).await else {
+            return Ok(());
+        };
+        let requests = request.unbatch();
+        if requests.len() == 1 {
+            return self.skip_failed_request(request, e).await;
+        }
+        if !Self::is_request_failure(&e) {
+            return Err(e);
+        }
+        warn!("Processing each request of {request} after a permanent error: {e:#}");
+        for request in requests {
+            if let Err(e) = self.process_request(&request).await {
+                self.skip_failed_request(&request, e).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Skips a request that failed with an error of its own, or returns the error aborting the crawl.
+    async fn skip_failed_request(&self, request: &Request, error: anyhow::Error) -> StdResult<()> {
+        if !Self::is_request_failure(&error) {
+            return Err(error);
+        }
+        warn!("Skipping request {request} after a permanent error: {error:#}");
+        self.state.increment_total_failed_requests(1).await;
+
+        Ok(())
+    }
+
+    /// Whether an error only fails the request that raised it, the crawl being aborted on the other errors.
+    ///
+    /// Only the missing owners, the rejected requests and the searches that can not be partitioned are skipped.
+    /// The invalid GraphQL queries, the rejected API tokens and the errors of the persister are global faults.
+    /// The nodes of a response that can not be parsed are already skipped by the fetcher.
+    fn is_request_failure(error: &anyhow::Error) -> bool {
+        matches!(
+            error.downcast_ref::<FetcherError>(),
+            Some(
+                FetcherError::NotFound(_)
+                    | FetcherError::Http {
+                        status: 400..=499,
+                        ..
+                    }
+                    | FetcherError::Partition(_)
+            )
+        )
     }
 
     async fn process_response(&self, response: &Response, request: &Request) -> StdResult<()> {
         self.state
-            .update_current_api_rate_limit(response.rate_limit().to_owned())
+            .update_api_rate_limit(
+                response.api_token().unwrap_or("default"),
+                response.rate_limit().to_owned(),
+            )
+            .await;
+        self.state
+            .increment_total_rate_limit_points_spent(response.rate_limit().cost.max(0) as u32)
             .await;
         let repositories = response.repositories();
         if repositories.is_empty() {
@@ -44,18 +151,63 @@ impl WorkerCrawler {
         for repository in repositories {
             info!("Fetched {repository}");
         }
-        let total_persisted_repositories_call = self.persister.persist(repositories).await?;
+        let report = self.persister.persist(repositories).await?;
         self.state
-            .increment_total_persisted_repositories(total_persisted_repositories_call)
+            .increment_total_persisted_repositories(report.total_inserted)
             .await;
         self.state
-            .increment_total_collisions_repositories(
-                repositories.len() as u32 - total_persisted_repositories_call,
-            )
+            .increment_total_collisions_repositories(report.total_collisions)
             .await;
+        let unreachable_node_ids = response.unreachable_node_ids();
+        if !unreachable_node_ids.is_empty() {
+            warn!(
+                "Marking {} deleted or inaccessible repositories",
+                unreachable_node_ids.len()
+            );
+            let total_marked = self
+                .persister
+                .mark_unreachable(unreachable_node_ids)
+                .await?;
+            self.state
+                .increment_total_unreachable_repositories(total_marked)
+                .await;
+        }
 
         Ok(())
     }
+
+    /// Attributes the owners found by a search, and then their repositories, to the seed query of the search.
+    async fn attribute_seed_query(
+        &self,
+        request: &Request,
+        response: &Response,
+        next_requests: &[Request],
+    ) {
+        match request {
+            Request::SearchOrganization(request) => {
+                let owner_names = next_requests
+                    .iter()
+                    .filter_map(Request::owner_name)
+                    .collect::<Vec<_>>();
+                self.state
+                    .assign_seed_query(&request.query, &owner_names)
+                    .await;
+            }
+            _ => {
+                let mut owner_yields = HashMap::new();
+                for repository in response.repositories() {
+                    *owner_yields
+                        .entry(repository.organization_name().as_str())
+                        .or_default() += 1;
+                }
+                for (owner_name, total_repositories) in owner_yields {
+                    self.state
+                        .increment_seed_query_yield(owner_name, total_repositories)
+                        .await;
+                }
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -65,25 +217,28 @@ impl RepositoryCrawler for WorkerCrawler {
             .set_total_repositories_target(total_repositories)
             .await;
         self.state.push_requests(requests).await;
-        while !self.state.has_completed().await? {
-            if let Some(request) = self.state.pop_request().await {
-                info!("Processing request: {request}");
-                self.state.increment_total_fetcher_calls(1).await;
-                match self.fetcher.fetch(&request).await? {
-                    Some((response, next_requests)) => {
-                        self.process_response(&response, &request).await?;
-                        self.state.push_requests(next_requests).await;
-                    }
-                    None => {}
-                }
-                self.state.acknowledge_request(&request).await;
-                warn!("{}", self.state.state_summary().await);
+        let result = self.process_requests().await;
+        // The buffered repositories are flushed even if the crawl failed, so that they are not lost
+        let flushed = self.persister.flush().await;
+        if let Err(e) = result {
+            if let Err(flush_error) = flushed {
+                warn!("Failed to flush the persister after a crawl failure: {flush_error}");
             }
+            return Err(e);
+        }
+        flushed?;
+        if self.state.is_shutdown_requested().await {
+            warn!("Crawler has stopped on shutdown");
+        } else {
+            warn!("Crawler has completed");
         }
-        warn!("Crawler has completed");
 
         Ok(())
     }
+
+    async fn flush(&self) -> StdResult<()> {
+        self.persister.flush().await
+    }
 }
 
 #[cfg(test)]
@@ -92,7 +247,9 @@ mod tests {
     use mockall::predicate::eq;
 
     use crate::{
-        FetcherRateLimit, MockRepositoryFetcher, MockRepositoryPersister, Repository, Response,
+        FetcherRateLimit, MockRepositoryFetcher, MockRepositoryPersister,
+        OrganizationRepositoriesRequest, PersistReport, PersisterError, Repository, Response,
+        SearchOrganizationRequest, SqlitePersister,
     };
 
     use super::*;
@@ -121,7 +278,12 @@ mod tests {
         };
         let persister = {
             let mut persister = MockRepositoryPersister::new();
-            persister.expect_persist().returning(|_| Ok(1)).times(1);
+            persister
+                .expect_persist()
+                .returning(|_| Ok(PersistReport::new(2, 1)))
+                .times(1);
+
+            persister.expect_flush().returning(|| Ok(())).times(1);
 
             persister
         };
@@ -149,7 +311,12 @@ mod tests {
 
             fetcher
         };
-        let persister = MockRepositoryPersister::new();
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            persister.expect_flush().returning(|| Ok(())).times(1);
+
+            persister
+        };
         let requests = vec![Request::dummy_search_organization()];
         let crawler = WorkerCrawler::new(
             Arc::new(fetcher),
@@ -163,6 +330,252 @@ mod tests {
             .expect_err("Crawler should fail if fetch task fails");
     }
 
+    #[tokio::test]
+    async fn crawler_fails_on_authentication_error() {
+        let fetcher = {
+            let mut fetcher = MockRepositoryFetcher::new();
+            fetcher
+                .expect_fetch()
+                .returning(|_| Err(anyhow!(FetcherError::Auth("Bad credentials".to_string()))))
+                .times(1);
+
+            fetcher
+        };
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            persister.expect_flush().returning(|| Ok(())).times(1);
+
+            persister
+        };
+        let requests = vec![Request::dummy_search_organization()];
+        let crawler = WorkerCrawler::new(
+            Arc::new(fetcher),
+            Arc::new(persister),
+            Arc::new(CrawlerState::default()),
+        );
+
+        crawler
+            .crawl(requests, 1)
+            .await
+            .expect_err("Crawler should fail on authentication error");
+    }
+
+    #[tokio::test]
+    async fn crawler_skips_request_failing_with_permanent_error() {
+        // The request with a cursor is popped first
+        let failing_request = Request::SearchOrganization(SearchOrganizationRequest::new(
+            "query-1",
+            10,
+            Some("cursor-1".to_string()),
+        ));
+        let request = Request::dummy_search_organization();
+        let fetcher = {
+            let mut fetcher = MockRepositoryFetcher::new();
+            fetcher
+                .expect_fetch()
+                .with(eq(failing_request.clone()))
+                .returning(|_| Err(anyhow!(FetcherError::NotFound("Not Found".to_string()))))
+                .times(1);
+            fetcher
+                .expect_fetch()
+                .with(eq(request.clone()))
+                .returning(|_| {
+                    Ok(Some((
+                        Response::new(
+                            vec![Repository::new("repository-1", "org-1", 10)],
+                            FetcherRateLimit::dummy(),
+                        ),
+                        vec![],
+                    )))
+                })
+                .times(1);
+
+            fetcher
+        };
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            persister
+                .expect_persist()
+                .returning(|_| Ok(PersistReport::new(1, 1)))
+                .times(1);
+            persister.expect_flush().returning(|| Ok(())).times(1);
+
+            persister
+        };
+        let state = Arc::new(CrawlerState::default());
+        let crawler = WorkerCrawler::new(Arc::new(fetcher), Arc::new(persister), state.clone());
+
+        crawler
+            .crawl(vec![failing_request, request], 1)
+            .await
+            .unwrap();
+
+        assert_eq!(1, state.get_total_failed_requests().await);
+        assert_eq!(1, state.get_total_persisted_repositories().await);
+    }
+
+    #[tokio::test]
+    async fn crawler_fails_on_invalid_query_error() {
+        let fetcher = {
+            let mut fetcher = MockRepositoryFetcher::new();
+            fetcher
+                .expect_fetch()
+                .returning(|_| {
+                    Err(anyhow!(FetcherError::Query(
+                        "Field 'foo' doesn't exist".to_string()
+                    )))
+                })
+                .times(1);
+
+            fetcher
+        };
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            persister.expect_flush().returning(|| Ok(())).times(1);
+
+            persister
+        };
+        let state = Arc::new(CrawlerState::default());
+        let crawler = WorkerCrawler::new(Arc::new(fetcher), Arc::new(persister), state.clone());
+
+        crawler
+            .crawl(
+                vec![
+                    Request::dummy_search_organization(),
+                    Request::SearchOrganization(SearchOrganizationRequest::new(
+                        "query-1",
+                        10,
+                        Some("cursor-1".to_string()),
+                    )),
+                ],
+                1,
+            )
+            .await
+            .expect_err("Crawler should fail on invalid query error");
+
+        assert_eq!(0, state.get_total_failed_requests().await);
+    }
+
+    #[tokio::test]
+    async fn crawler_fails_on_persister_query_error() {
+        let fetcher = {
+            let mut fetcher = MockRepositoryFetcher::new();
+            fetcher
+                .expect_fetch()
+                .returning(|_| {
+                    Ok(Some((
+                        Response::new(
+                            vec![Repository::new("repository-1", "org-1", 10)],
+                            FetcherRateLimit::dummy(),
+                        ),
+                        vec![],
+                    )))
+                })
+                .times(1);
+
+            fetcher
+        };
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            persister
+                .expect_persist()
+                .returning(|_| {
+                    Err(anyhow!(PersisterError::Query(
+                        "relation \"github.repository\" does not exist".to_string()
+                    )))
+                })
+                .times(1);
+            persister.expect_flush().returning(|| Ok(())).times(1);
+
+            persister
+        };
+        let state = Arc::new(CrawlerState::default());
+        let crawler = WorkerCrawler::new(Arc::new(fetcher), Arc::new(persister), state.clone());
+
+        crawler
+            .crawl(
+                vec![
+                    Request::dummy_search_organization(),
+                    Request::SearchOrganization(SearchOrganizationRequest::new(
+                        "query-1",
+                        10,
+                        Some("cursor-1".to_string()),
+                    )),
+                ],
+                1,
+            )
+            .await
+            .expect_err("Crawler should fail on persister query error");
+
+        assert_eq!(0, state.get_total_failed_requests().await);
+    }
+
+    #[tokio::test]
+    async fn crawler_processes_each_request_of_failed_batch() {
+        // The requests are popped in the reverse order of the organization names
+        let request_1 = OrganizationRepositoriesRequest::new("org-1", 10, None);
+        let request_2 = OrganizationRepositoriesRequest::new("org-2", 10, None);
+        let fetcher = {
+            let mut fetcher = MockRepositoryFetcher::new();
+            fetcher
+                .expect_fetch()
+                .with(eq(Request::OrganizationRepositoriesBatch(vec![
+                    request_2.clone(),
+                    request_1.clone(),
+                ])))
+                .returning(|_| Err(anyhow!(FetcherError::NotFound("Not Found".to_string()))))
+                .times(1);
+            fetcher
+                .expect_fetch()
+                .with(eq(Request::OrganizationRepositories(request_1.clone())))
+                .returning(|_| Err(anyhow!(FetcherError::NotFound("Not Found".to_string()))))
+                .times(1);
+            fetcher
+                .expect_fetch()
+                .with(eq(Request::OrganizationRepositories(request_2.clone())))
+                .returning(|_| {
+                    Ok(Some((
+                        Response::new(
+                            vec![Repository::new("repository-1", "org-2", 10)],
+                            FetcherRateLimit::dummy(),
+                        ),
+                        vec![],
+                    )))
+                })
+                .times(1);
+
+            fetcher
+        };
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            persister
+                .expect_persist()
+                .returning(|_| Ok(PersistReport::new(1, 1)))
+                .times(1);
+            persister.expect_flush().returning(|| Ok(())).times(1);
+
+            persister
+        };
+        let state = Arc::new(CrawlerState::default());
+        let crawler = WorkerCrawler::new(Arc::new(fetcher), Arc::new(persister), state.clone())
+            .with_batch_size(2);
+
+        crawler
+            .crawl(
+                vec![
+                    Request::OrganizationRepositories(request_1),
+                    Request::OrganizationRepositories(request_2),
+                ],
+                1,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(1, state.get_total_failed_requests().await);
+        assert_eq!(1, state.get_total_persisted_repositories().await);
+        assert_eq!(0, state.get_total_in_progress_requests().await);
+    }
+
     #[tokio::test]
     async fn crawler_fails_if_persist_task_fails() {
         let fetcher = {
@@ -189,6 +602,8 @@ mod tests {
                 .returning(|_| Err(anyhow!("Error persisting data")))
                 .times(1);
 
+            persister.expect_flush().returning(|| Ok(())).times(1);
+
             persister
         };
         let requests = vec![Request::dummy_search_organization()];
@@ -204,6 +619,36 @@ mod tests {
             .expect_err("Crawler should fail if one persist task fails");
     }
 
+    #[tokio::test]
+    async fn crawler_stops_popping_requests_when_shutdown_requested() {
+        let fetcher = {
+            let mut fetcher = MockRepositoryFetcher::new();
+            fetcher.expect_fetch().never();
+
+            fetcher
+        };
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            persister.expect_persist().never();
+            persister.expect_flush().returning(|| Ok(())).times(1);
+
+            persister
+        };
+        let state = Arc::new(CrawlerState::default());
+        state.request_shutdown().await;
+        let crawler = WorkerCrawler::new(Arc::new(fetcher), Arc::new(persister), state.clone());
+
+        crawler
+            .crawl(vec![Request::dummy_search_organization()], 10)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            Some(Request::dummy_search_organization()),
+            state.pop_request().await
+        );
+    }
+
     #[tokio::test]
     async fn crawler_success() {
         let fetcher = {
@@ -255,7 +700,7 @@ mod tests {
                     Repository::new("repository-1", "org-1", 10),
                     Repository::new("repository-2", "org-2", 20),
                 ]))
-                .returning(|_| Ok(2))
+                .returning(|_| Ok(PersistReport::new(2, 2)))
                 .times(1);
             persister
                 .expect_persist()
@@ -263,8 +708,9 @@ mod tests {
                     Repository::new("repository-2", "org-2", 20),
                     Repository::new("repository-3", "org-3", 30),
                 ]))
-                .returning(|_| Ok(1))
+                .returning(|_| Ok(PersistReport::new(2, 1)))
                 .times(1);
+            persister.expect_flush().returning(|| Ok(())).times(1);
 
             persister
         };
@@ -279,4 +725,176 @@ mod tests {
 
         crawler.crawl(requests, 3).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn crawler_attributes_repositories_to_seed_queries() {
+        let search_request =
+            Request::SearchOrganization(crate::SearchOrganizationRequest::new("query-1", 10, None));
+        let organization_request = Request::RepositoriesFromOrganization(
+            crate::RepositoriesFromOrganizationRequest::new("org-1", 10, None),
+        );
+        let fetcher = {
+            let mut fetcher = MockRepositoryFetcher::new();
+            let next_requests = vec![organization_request.clone()];
+            fetcher
+                .expect_fetch()
+                .with(eq(search_request.clone()))
+                .returning(move |_| {
+                    Ok(Some((
+                        Response::new(vec![], FetcherRateLimit::dummy()),
+                        next_requests.clone(),
+                    )))
+                })
+                .times(1);
+            fetcher
+                .expect_fetch()
+                .with(eq(organization_request))
+                .returning(|_| {
+                    Ok(Some((
+                        Response::new(
+                            vec![
+                                Repository::new("repository-1", "org-1", 10),
+                                Repository::new("repository-2", "org-1", 20),
+                            ],
+                            FetcherRateLimit::dummy(),
+                        ),
+                        vec![],
+                    )))
+                })
+                .times(1);
+
+            fetcher
+        };
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            persister.expect_persist().returning(|repositories| {
+                Ok(PersistReport::new(
+                    repositories.len() as u32,
+                    repositories.len() as u32,
+                ))
+            });
+            persister.expect_flush().returning(|| Ok(())).times(1);
+
+            persister
+        };
+        let state = Arc::new(CrawlerState::default());
+        let crawler = WorkerCrawler::new(Arc::new(fetcher), Arc::new(persister), state.clone());
+
+        crawler.crawl(vec![search_request], 2).await.unwrap();
+
+        assert_eq!(
+            std::collections::BTreeMap::from([("query-1".to_string(), 2)]),
+            state.get_seed_query_yields().await
+        );
+        assert_eq!(2, state.get_total_rate_limit_points_spent().await);
+    }
+
+    #[tokio::test]
+    async fn crawler_success_with_sqlite_persister() {
+        let fetcher = {
+            let mut fetcher = MockRepositoryFetcher::new();
+            fetcher
+                .expect_fetch()
+                .returning(|_| {
+                    Ok(Some((
+                        Response::new(
+                            vec![
+                                Repository::new("repository-1", "org-1", 10).with_node_id("R_1"),
+                                Repository::new("repository-2", "org-2", 20).with_node_id("R_2"),
+                            ],
+                            FetcherRateLimit::dummy(),
+                        ),
+                        vec![Request::SearchOrganization(
+                            crate::SearchOrganizationRequest::new(
+                                "org-1",
+                                10,
+                                Some("after".to_string()),
+                            ),
+                        )],
+                    )))
+                })
+                .times(1);
+            fetcher
+                .expect_fetch()
+                .returning(|_| {
+                    Ok(Some((
+                        Response::new(
+                            vec![
+                                Repository::new("repository-2", "org-2", 20).with_node_id("R_2"),
+                                Repository::new("repository-3", "org-3", 30).with_node_id("R_3"),
+                            ],
+                            FetcherRateLimit::dummy(),
+                        ),
+                        vec![],
+                    )))
+                })
+                .times(1);
+
+            fetcher
+        };
+        let persister = SqlitePersister::try_new("sqlite::memory:", chrono::Utc::now())
+            .await
+            .unwrap();
+        let requests = vec![Request::SearchOrganization(
+            crate::SearchOrganizationRequest::new("org-1", 10, None),
+        )];
+        let state = Arc::new(CrawlerState::default());
+        let crawler = WorkerCrawler::new(Arc::new(fetcher), Arc::new(persister), state.clone());
+
+        crawler.crawl(requests, 3).await.unwrap();
+
+        assert_eq!(3, state.get_total_persisted_repositories().await);
+        assert_eq!(1, state.get_total_collisions_repositories().await);
+    }
+
+    #[tokio::test]
+    async fn crawler_refreshes_known_repositories() {
+        let refresh_request =
+            Request::RefreshRepositories(crate::RefreshRepositoriesRequest::new(vec![
+                "R_1".to_string(),
+                "R_2".to_string(),
+            ]));
+        let fetcher = {
+            let mut fetcher = MockRepositoryFetcher::new();
+            fetcher
+                .expect_fetch()
+                .with(eq(refresh_request.clone()))
+                .returning(|_| {
+                    Ok(Some((
+                        Response::new(
+                            vec![Repository::new("repository-1", "org-1", 15).with_node_id("R_1")],
+                            FetcherRateLimit::dummy(),
+                        )
+                        .with_unreachable_node_ids(vec!["R_2".to_string()]),
+                        vec![],
+                    )))
+                })
+                .times(1);
+
+            fetcher
+        };
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            persister
+                .expect_persist()
+                .returning(|_| Ok(PersistReport::new(1, 0)))
+                .times(1);
+            persister
+                .expect_mark_unreachable()
+                .with(eq(vec!["R_2".to_string()]))
+                .returning(|node_ids| Ok(node_ids.len() as u32))
+                .times(1);
+            persister.expect_flush().returning(|| Ok(())).times(1);
+
+            persister
+        };
+        let state = Arc::new(CrawlerState::default());
+        let crawler = WorkerCrawler::new(Arc::new(fetcher), Arc::new(persister), state.clone());
+
+        crawler.crawl(vec![refresh_request], 2).await.unwrap();
+
+        assert_eq!(2, state.get_total_refreshed_repositories().await);
+        assert_eq!(1, state.get_total_unreachable_repositories().await);
+        assert_eq!(1, state.get_total_collisions_repositories().await);
+    }
 }
@@ -0,0 +1,37 @@
+use async_trait::async_trait;
+
+use crate::{CredentialProvider, StdResult};
+
+/// A credential provider that always provides the same API token.
+pub struct StaticTokenProvider {
+    token: String,
+}
+
+impl StaticTokenProvider {
+    /// Creates a new `StaticTokenProvider` instance with the given API token.
+    pub fn new(token: &str) -> Self {
+        Self {
+            token: token.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for StaticTokenProvider {
+    async fn token(&self) -> StdResult<String> {
+        Ok(self.token.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn token_is_static() {
+        let provider = StaticTokenProvider::new("secret");
+
+        assert_eq!("secret", provider.token().await.unwrap());
+        assert_eq!("secret", provider.token().await.unwrap());
+    }
+}
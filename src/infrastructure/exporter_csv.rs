@@ -0,0 +1,108 @@
+use std::io::Write;
+
+use crate::{ExportColumn, Repository, RepositoryExport, RepositoryExporter, StdResult};
+
+/// An exporter that writes repository data as CSV with a header row.
+pub struct CsvExporter;
+
+impl CsvExporter {
+    /// Creates a new `CsvExporter` instance.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for CsvExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RepositoryExporter for CsvExporter {
+    fn start<'a>(
+        &self,
+        columns: &'a [ExportColumn],
+        writer: &'a mut (dyn Write + Send),
+    ) -> StdResult<Box<dyn RepositoryExport + 'a>> {
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        csv_writer.write_record(columns.iter().map(|column| column.name()))?;
+
+        Ok(Box::new(CsvExport {
+            columns,
+            csv_writer,
+        }))
+    }
+}
+
+/// A CSV export in progress, whose header row is already written.
+struct CsvExport<'a> {
+    columns: &'a [ExportColumn],
+    csv_writer: csv::Writer<&'a mut (dyn Write + Send)>,
+}
+
+impl RepositoryExport for CsvExport<'_> {
+    fn write(&mut self, repositories: &[Repository]) -> StdResult<()> {
+        for repository in repositories {
+            self.csv_writer.write_record(
+                self.columns
+                    .iter()
+                    .map(|column| column.value(repository).to_text()),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> StdResult<()> {
+        self.csv_writer.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_csv() {
+        let mut buffer = Vec::new();
+        let repositories = vec![
+            Repository::new("repository-1", "org-1", 100),
+            Repository::new("repository,2", "org-2", 200),
+        ];
+
+        CsvExporter::new()
+            .export(
+                &repositories,
+                &[ExportColumn::RepositoryName, ExportColumn::TotalStars],
+                &mut buffer,
+            )
+            .unwrap();
+
+        assert_eq!(
+            "repository_name,total_stars\nrepository-1,100\n\"repository,2\",200\n",
+            String::from_utf8(buffer).unwrap()
+        );
+    }
+
+    #[test]
+    fn export_csv_in_batches() {
+        let mut buffer = Vec::new();
+        let columns = [ExportColumn::RepositoryName, ExportColumn::TotalStars];
+
+        let mut export = CsvExporter::new().start(&columns, &mut buffer).unwrap();
+        export
+            .write(&[Repository::new("repository-1", "org-1", 100)])
+            .unwrap();
+        export
+            .write(&[Repository::new("repository-2", "org-2", 200)])
+            .unwrap();
+        export.finish().unwrap();
+
+        assert_eq!(
+            "repository_name,total_stars\nrepository-1,100\nrepository-2,200\n",
+            String::from_utf8(buffer).unwrap()
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use std::io::{BufWriter, Write};
+
+use serde_json::{Map, Value};
+
+use crate::{ExportColumn, Repository, RepositoryExport, RepositoryExporter, StdResult};
+
+/// An exporter that writes repository data as JSON Lines, one JSON object per repository.
+pub struct JsonLinesExporter;
+
+impl JsonLinesExporter {
+    /// Creates a new `JsonLinesExporter` instance.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for JsonLinesExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RepositoryExporter for JsonLinesExporter {
+    fn start<'a>(
+        &self,
+        columns: &'a [ExportColumn],
+        writer: &'a mut (dyn Write + Send),
+    ) -> StdResult<Box<dyn RepositoryExport + 'a>> {
+        Ok(Box::new(JsonLinesExport {
+            columns,
+            writer: BufWriter::new(writer),
+        }))
+    }
+}
+
+/// A JSON Lines export in progress.
+struct JsonLinesExport<'a> {
+    columns: &'a [ExportColumn],
+    writer: BufWriter<&'a mut (dyn Write + Send)>,
+}
+
+impl RepositoryExport for JsonLinesExport<'_> {
+    fn write(&mut self, repositories: &[Repository]) -> StdResult<()> {
+        for repository in repositories {
+            let object = self
+                .columns
+                .iter()
+                .map(|column| {
+                    (
+                        column.name().to_string(),
+                        column.value(repository).to_json(),
+                    )
+                })
+                .collect::<Map<String, Value>>();
+            serde_json::to_writer(&mut self.writer, &object)?;
+            self.writer.write_all(b"\n")?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> StdResult<()> {
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_json_lines() {
+        let mut buffer = Vec::new();
+        let repositories = vec![
+            Repository::new("repository-1", "org-1", 100),
+            Repository::new("repository-2", "org-2", 200),
+        ];
+
+        JsonLinesExporter::new()
+            .export(
+                &repositories,
+                &[
+                    ExportColumn::RepositoryName,
+                    ExportColumn::TotalStars,
+                    ExportColumn::License,
+                ],
+                &mut buffer,
+            )
+            .unwrap();
+
+        assert_eq!(
+            concat!(
+                r#"{"repository_name":"repository-1","total_stars":100,"license":null}"#,
+                "\n",
+                r#"{"repository_name":"repository-2","total_stars":200,"license":null}"#,
+                "\n"
+            ),
+            String::from_utf8(buffer).unwrap()
+        );
+    }
+}
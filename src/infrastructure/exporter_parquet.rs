@@ -0,0 +1,238 @@
+use std::{io::Write, sync::Arc};
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Int64Array, RecordBatch, StringArray, TimestampMicrosecondArray,
+    builder::{ListBuilder, StringBuilder},
+};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use parquet::arrow::ArrowWriter;
+
+use crate::{
+    ExportColumn, ExportValue, Repository, RepositoryExport, RepositoryExporter, StdResult,
+};
+
+/// An exporter that writes repository data as a Parquet file.
+pub struct ParquetExporter;
+
+impl ParquetExporter {
+    /// Creates a new `ParquetExporter` instance.
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn data_type(column: ExportColumn) -> DataType {
+        match column {
+            ExportColumn::RepositoryName
+            | ExportColumn::OrganizationName
+            | ExportColumn::NodeId
+            | ExportColumn::PrimaryLanguage
+            | ExportColumn::License
+            | ExportColumn::Description
+            | ExportColumn::OwnerType => DataType::Utf8,
+            ExportColumn::TotalStars | ExportColumn::DatabaseId | ExportColumn::ForkCount => {
+                DataType::Int64
+            }
+            ExportColumn::IsFork | ExportColumn::IsArchived => DataType::Boolean,
+            ExportColumn::CreatedAt | ExportColumn::PushedAt => {
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+            }
+            ExportColumn::Topics => {
+                DataType::List(Arc::new(Field::new_list_field(DataType::Utf8, true)))
+            }
+        }
+    }
+
+    fn build_array(column: ExportColumn, repositories: &[Repository]) -> ArrayRef {
+        let values = repositories
+            .iter()
+            .map(|repository| column.value(repository))
+            .collect::<Vec<_>>();
+        match Self::data_type(column) {
+            DataType::Utf8 => {
+                Arc::new(StringArray::from_iter(values.into_iter().map(
+                    |value| match value {
+                        ExportValue::Text(value) => value,
+                        _ => None,
+                    },
+                )))
+            }
+            DataType::Int64 => {
+                Arc::new(Int64Array::from_iter(values.into_iter().map(
+                    |value| match value {
+                        ExportValue::Integer(value) => value,
+                        _ => None,
+                    },
+                )))
+            }
+            DataType::Boolean => Arc::new(BooleanArray::from_iter(values.into_iter().map(
+                |value| match value {
+                    ExportValue::Boolean(value) => value,
+                    _ => None,
+                },
+            ))),
+            DataType::Timestamp(_, _) => Arc::new(
+                TimestampMicrosecondArray::from_iter(values.into_iter().map(|value| match value {
+                    ExportValue::Timestamp(value) => value.map(|value| value.timestamp_micros()),
+                    _ => None,
+                }))
+                .with_timezone("UTC"),
+            ),
+            _ => {
+                let mut builder = ListBuilder::new(StringBuilder::new());
+                for value in values {
+                    match value {
+                        ExportValue::TextList(Some(items)) => {
+                            builder.append_value(items.into_iter().map(Some));
+                        }
+                        _ => builder.append_null(),
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+        }
+    }
+}
+
+impl Default for ParquetExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RepositoryExporter for ParquetExporter {
+    fn start<'a>(
+        &self,
+        columns: &'a [ExportColumn],
+        writer: &'a mut (dyn Write + Send),
+    ) -> StdResult<Box<dyn RepositoryExport + 'a>> {
+        let schema = Arc::new(Schema::new(
+            columns
+                .iter()
+                .map(|column| Field::new(column.name(), Self::data_type(*column), true))
+                .collect::<Vec<_>>(),
+        ));
+        let arrow_writer = ArrowWriter::try_new(writer, schema.clone(), None)?;
+
+        Ok(Box::new(ParquetExport {
+            columns,
+            schema,
+            arrow_writer,
+        }))
+    }
+}
+
+/// A Parquet export in progress, each batch of repositories being written as a record batch.
+struct ParquetExport<'a> {
+    columns: &'a [ExportColumn],
+    schema: Arc<Schema>,
+    arrow_writer: ArrowWriter<&'a mut (dyn Write + Send)>,
+}
+
+impl RepositoryExport for ParquetExport<'_> {
+    fn write(&mut self, repositories: &[Repository]) -> StdResult<()> {
+        let record_batch = RecordBatch::try_new(
+            self.schema.clone(),
+            self.columns
+                .iter()
+                .map(|column| ParquetExporter::build_array(*column, repositories))
+                .collect(),
+        )?;
+        self.arrow_writer.write(&record_batch)?;
+
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> StdResult<()> {
+        self.arrow_writer.close()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use crate::RepositoryMetadata;
+
+    use super::*;
+
+    #[test]
+    fn export_parquet() {
+        let mut buffer = Vec::new();
+        let repositories = vec![
+            Repository::new("repository-1", "org-1", 100).with_metadata(RepositoryMetadata {
+                topics: Some(vec!["rust".to_string()]),
+                ..RepositoryMetadata::default()
+            }),
+            Repository::new("repository-2", "org-2", 200),
+        ];
+
+        ParquetExporter::new()
+            .export(
+                &repositories,
+                &[
+                    ExportColumn::RepositoryName,
+                    ExportColumn::TotalStars,
+                    ExportColumn::IsFork,
+                    ExportColumn::CreatedAt,
+                    ExportColumn::Topics,
+                ],
+                &mut buffer,
+            )
+            .unwrap();
+
+        let record_batch = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(buffer))
+            .unwrap()
+            .build()
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(2, record_batch.num_rows());
+        assert_eq!(5, record_batch.num_columns());
+        assert_eq!(
+            &Int64Array::from(vec![100, 200]),
+            record_batch
+                .column_by_name("total_stars")
+                .unwrap()
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap()
+        );
+        assert_eq!(
+            1,
+            record_batch.column_by_name("topics").unwrap().null_count()
+        );
+    }
+
+    #[test]
+    fn export_parquet_in_batches() {
+        let mut buffer = Vec::new();
+        let columns = [ExportColumn::RepositoryName, ExportColumn::TotalStars];
+
+        let mut export = ParquetExporter::new().start(&columns, &mut buffer).unwrap();
+        export
+            .write(&[Repository::new("repository-1", "org-1", 100)])
+            .unwrap();
+        export
+            .write(&[Repository::new("repository-2", "org-2", 200)])
+            .unwrap();
+        export.finish().unwrap();
+
+        let record_batches = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(buffer))
+            .unwrap()
+            .build()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            2,
+            record_batches
+                .iter()
+                .map(|record_batch| record_batch.num_rows())
+                .sum::<usize>()
+        );
+    }
+}
@@ -1,33 +1,96 @@
 #![allow(non_snake_case)]
 
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Write as _, sync::Arc, time::Duration};
 
-use anyhow::{Context, anyhow};
-use gql_client::{Client, GraphQLError};
-use log::error;
-use serde::{Deserialize, Serialize};
-use thiserror::Error;
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use log::{error, warn};
+use reqwest::header::{HeaderMap, RETRY_AFTER, USER_AGENT};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
 use crate::{
-    FetcherRateLimit, RepositoriesFromOrganizationRequest, Repository, RepositoryFetcher, Request,
-    Response, SearchOrganizationRequest, StdResult,
+    CrawlerState, FetcherError, FetcherRateLimit, HttpRateLimitHeaders,
+    OrganizationRepositoriesRequest, OrganizationTraversal, OwnerType, RefreshRepositoriesRequest,
+    RepositoriesFromOrganizationRequest, RepositoriesFromUserRequest, Repository,
+    RepositoryFetcher, RepositoryField, RepositoryMetadata, Request, Response,
+    SearchOrganizationRequest, StdResult, TokenPool,
 };
 
 /// The GraphQL production endpoint for GitHub.
 pub const GITHUB_GRAPHQL_ENDPOINT: &str = "https://api.github.com/graphql";
 
-const SEARCH_QUERY: &str = r#"
-query ($query: String!, $first: Int!, $after: String) {
+/// The maximum number of results that the GitHub search API returns for a query.
+const GITHUB_SEARCH_RESULTS_CAP: u32 = 1000;
+
+/// The GraphQL variables of the optional repository fields of a query.
+macro_rules! repository_fields_variables {
+    () => {
+        r#"
+  $topicsFirst: Int!
+  $withDatabaseId: Boolean!
+  $withPrimaryLanguage: Boolean!
+  $withForkCount: Boolean!
+  $withIsFork: Boolean!
+  $withIsArchived: Boolean!
+  $withCreatedAt: Boolean!
+  $withPushedAt: Boolean!
+  $withLicense: Boolean!
+  $withDescription: Boolean!
+  $withTopics: Boolean!
+"#
+    };
+}
+
+/// The GraphQL fragment of the repository fields, shared by the queries.
+macro_rules! repository_fields_fragment {
+    () => {
+        r#"
+fragment RepositoryFields on Repository {
+  id
+  name
+  owner {
+    login
+    __typename
+  }
+  stargazerCount
+  databaseId @include(if: $withDatabaseId)
+  primaryLanguage @include(if: $withPrimaryLanguage) {
+    name
+  }
+  forkCount @include(if: $withForkCount)
+  isFork @include(if: $withIsFork)
+  isArchived @include(if: $withIsArchived)
+  createdAt @include(if: $withCreatedAt)
+  pushedAt @include(if: $withPushedAt)
+  licenseInfo @include(if: $withLicense) {
+    spdxId
+  }
+  description @include(if: $withDescription)
+  repositoryTopics(first: $topicsFirst) @include(if: $withTopics) {
+    nodes {
+      topic {
+        name
+      }
+    }
+  }
+}
+"#
+    };
+}
+
+const SEARCH_QUERY: &str = concat!(
+    r#"
+query (
+  $query: String!
+  $first: Int!
+  $after: String"#,
+    repository_fields_variables!(),
+    r#") {
   search(query: $query, type: REPOSITORY, first: $first, after: $after) {
+    repositoryCount
     edges {
       node {
-        ... on Repository {
-          name
-          owner {
-            login
-          }
-          stargazerCount
-        }
+        ...RepositoryFields
       }
     }
     pageInfo {
@@ -42,56 +105,309 @@ query ($query: String!, $first: Int!, $after: String) {
     resetAt
   }
 }
-"#;
-
-/// Fetcher error
-#[derive(Error, Debug)]
-pub enum FetcherError {
-    /// Parse error
-    #[error("Parsing error: {0}")]
-    Parse(String),
-    /// Remote error
-    #[error("Remote error: {0}")]
-    Remote(String),
-}
-
-impl Into<FetcherError> for GraphQLError {
-    fn into(self) -> FetcherError {
-        let message = self.message().to_string();
-        match message.contains("Failed to parse response") {
-            true => FetcherError::Parse(message),
-            false => FetcherError::Remote(message),
-        }
+"#,
+    repository_fields_fragment!()
+);
+
+const REFRESH_QUERY: &str = concat!(
+    r#"
+query (
+  $ids: [ID!]!"#,
+    repository_fields_variables!(),
+    r#") {
+  nodes(ids: $ids) {
+    ...RepositoryFields
+  }
+  rateLimit {
+    limit
+    cost
+    remaining
+    resetAt
+  }
+}
+"#,
+    repository_fields_fragment!()
+);
+
+/// The maximum number of node identifiers that a `nodes` query can request from the GitHub API.
+pub const GITHUB_MAX_NODE_IDS_PER_QUERY: usize = 100;
+
+/// The number of nodes above which a batched query risks timing out, well under the 500,000 nodes per query of the GitHub API.
+const GITHUB_MAX_NODES_PER_BATCH: u32 = 10_000;
+
+/// The number of topics requested per repository, passed as the `$topicsFirst` variable of the repository fields fragment.
+const REPOSITORY_TOPICS_FIRST: u32 = 20;
+
+/// Builds a GraphQL query walking the repositories connections of several organizations, aliased `o0`, `o1`, etc.
+fn organization_repositories_query(total_organizations: usize) -> String {
+    let mut variables = String::new();
+    let mut selections = String::new();
+    for index in 0..total_organizations {
+        let _ = write!(
+            variables,
+            "\n  $login{index}: String!\n  $first{index}: Int!\n  $after{index}: String"
+        );
+        let _ = write!(
+            selections,
+            r#"
+  o{index}: organization(login: $login{index}) {{
+    repositories(
+      first: $first{index}
+      after: $after{index}
+      privacy: PUBLIC
+      orderBy: {{ field: STARGAZERS, direction: DESC }}
+    ) {{
+      nodes {{
+        ...RepositoryFields
+      }}
+      pageInfo {{
+        endCursor
+        hasNextPage
+      }}
+    }}
+  }}"#
+        );
+    }
+
+    format!(
+        r#"
+query ({variables}{}) {{{selections}
+  rateLimit {{
+    limit
+    cost
+    remaining
+    resetAt
+  }}
+}}
+{}"#,
+        repository_fields_variables!(),
+        repository_fields_fragment!()
+    )
+}
+
+/// A GraphQL request sent to the API.
+#[derive(Debug, Serialize)]
+struct GraphQlRequest<'a, T: Serialize> {
+    query: &'a str,
+    variables: T,
+}
+
+/// A GraphQL response received from the API.
+#[derive(Deserialize, Debug)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<GraphQlErrorMessage>>,
+}
+
+/// An error of a GraphQL response.
+#[derive(Deserialize, Debug)]
+struct GraphQlErrorMessage {
+    message: String,
+
+    /// The type of the error raised while executing the query (e.g., `NOT_FOUND`).
+    #[serde(rename = "type")]
+    error_type: Option<String>,
+
+    /// The extensions of the error, with a `code` for the validation errors of the query.
+    extensions: Option<serde_json::Value>,
+
+    /// The path of the field in error within the data.
+    #[serde(default)]
+    path: Vec<serde_json::Value>,
+}
+
+/// Classifies the errors of a GraphQL response by their type.
+fn classify_graphql_errors(
+    errors: Vec<GraphQlErrorMessage>,
+    headers: HttpRateLimitHeaders,
+) -> FetcherError {
+    let has_error_type = |error_type: &str| {
+        errors
+            .iter()
+            .any(|error| error.error_type.as_deref() == Some(error_type))
+    };
+    let is_invalid_query = errors.iter().any(|error| {
+        error
+            .extensions
+            .as_ref()
+            .is_some_and(|extensions| extensions.get("code").is_some())
+    });
+    let is_rate_limited = has_error_type("RATE_LIMITED");
+    let is_not_found = has_error_type("NOT_FOUND");
+    let is_forbidden = has_error_type("FORBIDDEN");
+    let message = errors
+        .into_iter()
+        .map(|error| error.message)
+        .collect::<Vec<_>>()
+        .join(", ");
+    match () {
+        _ if is_rate_limited => FetcherError::RateLimited {
+            status: 200,
+            headers,
+            message,
+        },
+        _ if is_invalid_query => FetcherError::Query(message),
+        _ if is_forbidden => FetcherError::Auth(message),
+        _ if is_not_found => FetcherError::NotFound(message),
+        _ => FetcherError::GraphQl(message),
     }
 }
 
+/// The data of a GraphQL query response, reporting the rate limit of the API.
+trait GraphQlQueryData: DeserializeOwned {
+    fn rate_limit(&self) -> &RateLimit;
+}
+
 #[derive(Deserialize, Debug)]
 struct SearchQueryData {
     search: SearchResult,
     rateLimit: RateLimit,
 }
 
+impl GraphQlQueryData for SearchQueryData {
+    fn rate_limit(&self) -> &RateLimit {
+        &self.rateLimit
+    }
+}
+
 #[derive(Deserialize, Debug)]
-struct SearchResult {
-    edges: Vec<Option<SearchEdge>>,
+struct OrganizationsQueryData {
+    /// The organizations by alias, missing if they do not exist anymore.
+    #[serde(flatten)]
+    organizations: HashMap<String, Option<OrganizationResult>>,
+    rateLimit: RateLimit,
+}
+
+impl GraphQlQueryData for OrganizationsQueryData {
+    fn rate_limit(&self) -> &RateLimit {
+        &self.rateLimit
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct RefreshQueryData {
+    /// The repositories in the order of the requested node identifiers, missing if they are deleted or inaccessible.
+    nodes: Vec<Option<serde_json::Value>>,
+    rateLimit: RateLimit,
+}
+
+impl GraphQlQueryData for RefreshQueryData {
+    fn rate_limit(&self) -> &RateLimit {
+        &self.rateLimit
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct OrganizationResult {
+    repositories: RepositoryConnection,
+}
+
+#[derive(Deserialize, Debug)]
+struct RepositoryConnection {
+    /// The nodes are parsed one by one, so that an invalid node does not drop the whole page.
+    nodes: Vec<Option<serde_json::Value>>,
     pageInfo: PageInfo,
 }
 
 #[derive(Deserialize, Debug)]
-struct SearchEdge {
-    node: RepositoryNode,
+struct SearchResult {
+    repositoryCount: u32,
+    /// The edges are parsed one by one, so that an invalid edge does not drop the whole page.
+    edges: Vec<Option<serde_json::Value>>,
+    pageInfo: PageInfo,
+}
+
+/// Extracts the nodes of the edges of the search results.
+fn edge_nodes(edges: Vec<Option<serde_json::Value>>) -> Vec<Option<serde_json::Value>> {
+    edges
+        .into_iter()
+        .map(|edge| edge.and_then(|mut edge| edge.get_mut("node").map(serde_json::Value::take)))
+        .collect()
 }
 
 #[derive(Deserialize, Debug)]
 struct RepositoryNode {
+    id: String,
     name: String,
     owner: Owner,
     stargazerCount: u32,
+    databaseId: Option<i64>,
+    primaryLanguage: Option<Language>,
+    forkCount: Option<u32>,
+    isFork: Option<bool>,
+    isArchived: Option<bool>,
+    createdAt: Option<DateTime<Utc>>,
+    pushedAt: Option<DateTime<Utc>>,
+    licenseInfo: Option<License>,
+    description: Option<String>,
+    repositoryTopics: Option<RepositoryTopics>,
+}
+
+impl From<RepositoryNode> for RepositoryMetadata {
+    fn from(node: RepositoryNode) -> Self {
+        Self {
+            database_id: node.databaseId,
+            primary_language: node.primaryLanguage.map(|language| language.name),
+            fork_count: node.forkCount,
+            is_fork: node.isFork,
+            is_archived: node.isArchived,
+            created_at: node.createdAt,
+            pushed_at: node.pushedAt,
+            license: node.licenseInfo.and_then(|license| license.spdxId),
+            description: node.description,
+            topics: node.repositoryTopics.map(|topics| {
+                topics
+                    .nodes
+                    .into_iter()
+                    .flatten()
+                    .map(|node| node.topic.name)
+                    .collect()
+            }),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
 struct Owner {
     login: String,
+
+    /// The GraphQL type of the owner, `Organization` or `User`.
+    #[serde(rename = "__typename")]
+    typename: Option<String>,
+}
+
+impl Owner {
+    fn owner_type(&self) -> OwnerType {
+        match self.typename.as_deref() {
+            Some("User") => OwnerType::User,
+            _ => OwnerType::Organization,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Language {
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct License {
+    spdxId: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RepositoryTopics {
+    nodes: Vec<Option<RepositoryTopicNode>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RepositoryTopicNode {
+    topic: Topic,
+}
+
+#[derive(Deserialize, Debug)]
+struct Topic {
+    name: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -100,7 +416,7 @@ struct PageInfo {
     hasNextPage: bool,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 struct RateLimit {
     limit: i32,
     cost: i32,
@@ -128,14 +444,25 @@ struct GraphQlSearchQuery {
     pub(super) first: u16,
     /// The cursor for pagination.
     pub(super) after: Option<String>,
+    /// The optional repository fields to include in the results.
+    #[serde(flatten)]
+    pub(super) fields: GraphQlRepositoryFields,
+}
+
+impl GraphQlSearchQuery {
+    fn with_repository_fields(mut self, fields: &[RepositoryField]) -> Self {
+        self.fields = GraphQlRepositoryFields::from(fields);
+        self
+    }
 }
 
 impl From<&SearchOrganizationRequest> for GraphQlSearchQuery {
     fn from(request: &SearchOrganizationRequest) -> Self {
         Self {
-            query: request.query.to_owned(),
+            query: request.search_query(),
             first: request.first,
             after: request.after.to_owned(),
+            fields: GraphQlRepositoryFields::default(),
         }
     }
 }
@@ -145,138 +472,672 @@ impl From<&RepositoriesFromOrganizationRequest> for GraphQlSearchQuery {
             query: format!("org:{} stars:>0", request.organization_name),
             first: request.first,
             after: request.after.to_owned(),
+            fields: GraphQlRepositoryFields::default(),
+        }
+    }
+}
+
+/// A GraphQL query for walking the repositories of several organizations
+#[derive(Debug, Serialize)]
+struct GraphQlOrganizationsQuery {
+    /// The login, number of repositories to return and cursor of each organization, suffixed by its index.
+    #[serde(flatten)]
+    organizations: serde_json::Map<String, serde_json::Value>,
+    /// The optional repository fields to include in the results.
+    #[serde(flatten)]
+    fields: GraphQlRepositoryFields,
+}
+
+impl GraphQlOrganizationsQuery {
+    fn with_repository_fields(mut self, fields: &[RepositoryField]) -> Self {
+        self.fields = GraphQlRepositoryFields::from(fields);
+        self
+    }
+}
+
+impl From<&[OrganizationRepositoriesRequest]> for GraphQlOrganizationsQuery {
+    fn from(requests: &[OrganizationRepositoriesRequest]) -> Self {
+        let mut organizations = serde_json::Map::new();
+        for (index, request) in requests.iter().enumerate() {
+            organizations.insert(
+                format!("login{index}"),
+                request.organization_name.to_owned().into(),
+            );
+            organizations.insert(format!("first{index}"), request.first.into());
+            organizations.insert(format!("after{index}"), request.after.to_owned().into());
+        }
+
+        Self {
+            organizations,
+            fields: GraphQlRepositoryFields::default(),
+        }
+    }
+}
+
+/// A GraphQL query for fetching known repositories by their node identifiers
+#[derive(Debug, Serialize)]
+struct GraphQlRefreshQuery {
+    /// The node identifiers of the repositories.
+    ids: Vec<String>,
+    /// The optional repository fields to include in the results.
+    #[serde(flatten)]
+    fields: GraphQlRepositoryFields,
+}
+
+impl GraphQlRefreshQuery {
+    fn with_repository_fields(mut self, fields: &[RepositoryField]) -> Self {
+        self.fields = GraphQlRepositoryFields::from(fields);
+        self
+    }
+}
+
+impl From<&RefreshRepositoriesRequest> for GraphQlRefreshQuery {
+    fn from(request: &RefreshRepositoriesRequest) -> Self {
+        Self {
+            ids: request.node_ids.to_owned(),
+            fields: GraphQlRepositoryFields::default(),
+        }
+    }
+}
+
+impl From<&RepositoriesFromUserRequest> for GraphQlSearchQuery {
+    fn from(request: &RepositoriesFromUserRequest) -> Self {
+        Self {
+            query: format!("user:{} stars:>0", request.user_name),
+            first: request.first,
+            after: request.after.to_owned(),
+            fields: GraphQlRepositoryFields::default(),
+        }
+    }
+}
+
+/// The GraphQL variables of the optional repository fields of a query
+#[derive(Debug, Serialize)]
+struct GraphQlRepositoryFields {
+    topicsFirst: u32,
+    withDatabaseId: bool,
+    withPrimaryLanguage: bool,
+    withForkCount: bool,
+    withIsFork: bool,
+    withIsArchived: bool,
+    withCreatedAt: bool,
+    withPushedAt: bool,
+    withLicense: bool,
+    withDescription: bool,
+    withTopics: bool,
+}
+
+impl From<&[RepositoryField]> for GraphQlRepositoryFields {
+    fn from(fields: &[RepositoryField]) -> Self {
+        Self {
+            topicsFirst: REPOSITORY_TOPICS_FIRST,
+            withDatabaseId: fields.contains(&RepositoryField::DatabaseId),
+            withPrimaryLanguage: fields.contains(&RepositoryField::PrimaryLanguage),
+            withForkCount: fields.contains(&RepositoryField::ForkCount),
+            withIsFork: fields.contains(&RepositoryField::IsFork),
+            withIsArchived: fields.contains(&RepositoryField::IsArchived),
+            withCreatedAt: fields.contains(&RepositoryField::CreatedAt),
+            withPushedAt: fields.contains(&RepositoryField::PushedAt),
+            withLicense: fields.contains(&RepositoryField::License),
+            withDescription: fields.contains(&RepositoryField::Description),
+            withTopics: fields.contains(&RepositoryField::Topics),
         }
     }
 }
 
+impl Default for GraphQlRepositoryFields {
+    fn default() -> Self {
+        Self::from(&[][..])
+    }
+}
+
+/// Parses the `Retry-After` and `x-ratelimit-*` headers of a response.
+fn parse_rate_limit_headers(headers: &HeaderMap, now: DateTime<Utc>) -> HttpRateLimitHeaders {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    };
+    let retry_after = header(RETRY_AFTER.as_str()).and_then(|value| {
+        value
+            .parse::<u64>()
+            .map(Duration::from_secs)
+            .ok()
+            .or_else(|| {
+                DateTime::parse_from_rfc2822(value).ok().map(|retry_at| {
+                    (retry_at.with_timezone(&Utc) - now)
+                        .to_std()
+                        .unwrap_or_default()
+                })
+            })
+    });
+
+    HttpRateLimitHeaders {
+        retry_after,
+        limit: header("x-ratelimit-limit").and_then(|value| value.parse().ok()),
+        remaining: header("x-ratelimit-remaining").and_then(|value| value.parse().ok()),
+        reset_at: header("x-ratelimit-reset")
+            .and_then(|value| value.parse().ok())
+            .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0)),
+    }
+}
+
 /// Fetches repository data from a GraphQL API.
 pub struct GraphQlFetcher {
-    client: Client,
+    /// The GraphQL endpoint.
+    endpoint: String,
+
+    /// The HTTP client sending the queries.
+    client: reqwest::Client,
+
+    /// The pool of API tokens shared between the fetchers.
+    token_pool: Arc<TokenPool>,
+
+    /// The optional repository fields to fetch.
+    repository_fields: Vec<RepositoryField>,
+
+    /// The optional state of the crawler, counting the skipped responses.
+    state: Option<Arc<CrawlerState>>,
+
+    /// How the repositories of the organizations found by a search are fetched.
+    organization_traversal: OrganizationTraversal,
 }
 
 impl GraphQlFetcher {
-    /// Creates a new `GraphQlFetcher` instance with the given GraphQL client.
+    /// Creates a new `GraphQlFetcher` instance with the given GraphQL endpoint and token pool.
+    pub fn new(endpoint: &str, token_pool: Arc<TokenPool>) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            client: reqwest::Client::new(),
+            token_pool,
+            repository_fields: vec![],
+            state: None,
+            organization_traversal: OrganizationTraversal::default(),
+        }
+    }
+
+    /// Creates a new `GraphQlFetcher` instance with the tokens of the `GITHUB_API_TOKEN` environment variable.
     pub fn try_new(endpoint: &str) -> StdResult<Self> {
-        let github_api_token = std::env::var("GITHUB_API_TOKEN")
-            .with_context(|| "Missing GITHUB_API_TOKEN environment variable")?;
-        let bearer_token = format!("Bearer {}", github_api_token);
-        let mut headers = HashMap::from([("User-Agent", "gql-client")]);
-        headers.insert("Authorization", &bearer_token);
-        let client = Client::new_with_headers(endpoint, headers);
+        Ok(Self::new(endpoint, Arc::new(TokenPool::try_from_env()?)))
+    }
+
+    /// Sets the optional repository fields to fetch.
+    pub fn with_repository_fields(mut self, repository_fields: &[RepositoryField]) -> Self {
+        self.repository_fields = repository_fields.to_vec();
+        self
+    }
+
+    /// Sets how the repositories of the organizations found by a search are fetched.
+    pub fn with_organization_traversal(
+        mut self,
+        organization_traversal: OrganizationTraversal,
+    ) -> Self {
+        self.organization_traversal = organization_traversal;
+        self
+    }
+
+    /// Sets the state of the crawler in which the responses skipped on parse errors are counted.
+    pub fn with_state(mut self, state: Arc<CrawlerState>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    async fn query<T: GraphQlQueryData>(
+        &self,
+        query: &str,
+        variables: impl Serialize,
+    ) -> Result<(T, String), FetcherError> {
+        let token = self.token_pool.acquire().await;
+        let fetched_data: T = match self.send_query(token, query, variables).await {
+            Ok((fetched_data, errors)) => {
+                if !errors.is_empty() {
+                    for error in &errors {
+                        warn!(
+                            "Skipped node of partial GraphQL response at {:?}: {}",
+                            error.path, error.message
+                        );
+                    }
+                    if let Some(state) = &self.state {
+                        state.increment_total_node_errors(errors.len() as u32).await;
+                    }
+                }
+                fetched_data
+            }
+            Err(e) => {
+                self.token_pool.release(token).await;
+                // Every rate limit pauses the workers, including the ones reported as GraphQL errors
+                if let Some(retry_delay) = e.retry_delay(Utc::now()) {
+                    warn!(
+                        "API rate limited with {}: {e}",
+                        self.token_pool.label(token)
+                    );
+                    self.token_pool.throttle(retry_delay).await;
+                }
+                return Err(e);
+            }
+        };
+        self.token_pool
+            .update(token, fetched_data.rate_limit().clone().into())
+            .await;
+
+        Ok((fetched_data, self.token_pool.label(token).to_string()))
+    }
+
+    async fn send_query<T: GraphQlQueryData>(
+        &self,
+        token: usize,
+        query: &str,
+        variables: impl Serialize,
+    ) -> Result<(T, Vec<GraphQlErrorMessage>), FetcherError> {
+        let secret = self
+            .token_pool
+            .secret(token)
+            .await
+            .map_err(FetcherError::Credentials)?;
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .header(USER_AGENT, "github-crawler")
+            .bearer_auth(secret)
+            .json(&GraphQlRequest { query, variables })
+            .send()
+            .await
+            .map_err(|e| FetcherError::Network(e.to_string()))?;
+        let status = response.status();
+        let headers = parse_rate_limit_headers(response.headers(), Utc::now());
+        let body = response
+            .text()
+            .await
+            .map_err(|e| FetcherError::Network(e.to_string()))?;
+        if !status.is_success() {
+            return Err(FetcherError::from_http_status(
+                status.as_u16(),
+                headers,
+                body,
+            ));
+        }
+        let response: GraphQlResponse<serde_json::Value> =
+            serde_json::from_str(&body).map_err(|e| {
+                FetcherError::Parse(format!(
+                    "Failed to parse response: {e}. The response body is: {body}"
+                ))
+            })?;
+        let errors = response.errors.unwrap_or_default();
+        // The data of a partial response is kept along its errors, as long as the results are present
+        match response
+            .data
+            .filter(|data| !data.is_null())
+            .map(serde_json::from_value::<T>)
+        {
+            Some(Ok(data)) => Ok((data, errors)),
+            _ if !errors.is_empty() => Err(classify_graphql_errors(errors, headers)),
+            Some(Err(e)) => Err(FetcherError::Parse(format!(
+                "Failed to parse response: {e}. The response body is: {body}"
+            ))),
+            None => Err(FetcherError::Parse(format!(
+                "Missing data in response: {body}"
+            ))),
+        }
+    }
 
-        Ok(Self { client })
+    async fn search(
+        &self,
+        variables: GraphQlSearchQuery,
+    ) -> Result<(SearchQueryData, String), FetcherError> {
+        self.query(SEARCH_QUERY, variables).await
+    }
+
+    /// Parses the repository nodes of the results, skipping the missing and invalid ones.
+    async fn parse_nodes(&self, nodes: Vec<Option<serde_json::Value>>) -> Vec<RepositoryNode> {
+        let mut total_invalid_nodes = 0;
+        let nodes = nodes
+            .into_iter()
+            .flatten()
+            .filter(|node| !node.is_null())
+            .filter_map(|node| match serde_json::from_value(node) {
+                Ok(node) => Some(node),
+                Err(e) => {
+                    error!("Failed to parse GraphQL repository node: {e}");
+                    total_invalid_nodes += 1;
+                    None
+                }
+            })
+            .collect();
+        if total_invalid_nodes > 0
+            && let Some(state) = &self.state
+        {
+            state
+                .increment_total_parse_errors(total_invalid_nodes)
+                .await;
+        }
+
+        nodes
+    }
+
+    /// Builds the repositories of an owner from their nodes, skipping the missing and invalid ones.
+    async fn build_repositories(
+        &self,
+        nodes: Vec<Option<serde_json::Value>>,
+        owner_name: &str,
+        owner_type: OwnerType,
+    ) -> Vec<Repository> {
+        let observed_at = Utc::now();
+        self.parse_nodes(nodes)
+            .await
+            .into_iter()
+            .map(|node| {
+                Repository::new(&node.name, owner_name, node.stargazerCount)
+                    .with_node_id(&node.id)
+                    .with_owner_type(owner_type)
+                    .with_observed_at(observed_at)
+                    .with_metadata(node.into())
+            })
+            .collect()
     }
 
     async fn fetch_organizations(
         &self,
         request: &SearchOrganizationRequest,
     ) -> StdResult<Option<(Response, Vec<Request>)>> {
-        let fetched_data = self
-            .client
-            .query_with_vars_unwrap::<SearchQueryData, GraphQlSearchQuery>(
-                SEARCH_QUERY,
-                request.into(),
-            )
-            .await
-            .map_err(|e| e.into());
-        match fetched_data {
-            Err(FetcherError::Parse(e)) => {
-                error!("Failed to parse GraphQL response: {}", e);
-                return Ok(None);
+        let fetched_data = self.search(request.into()).await;
+        if let Err(FetcherError::Parse(e)) = fetched_data {
+            error!("Failed to parse GraphQL response: {}", e);
+            if let Some(state) = &self.state {
+                state.increment_total_parse_errors(1).await;
             }
-            _ => {}
+            return Ok(None);
         }
-        let fetched_data = fetched_data.map_err(|e| anyhow!(e))?;
+        let (fetched_data, api_token) = fetched_data.map_err(|e| anyhow!(e))?;
         if fetched_data.search.edges.is_empty() {
             return Ok(None);
         }
 
-        let mut next_requests = fetched_data
-            .search
-            .edges
+        let mut next_requests = self
+            .parse_nodes(edge_nodes(fetched_data.search.edges))
+            .await
             .into_iter()
-            .filter_map(|edge| {
-                edge.map(|edge| {
-                    Request::RepositoriesFromOrganization(RepositoriesFromOrganizationRequest::new(
-                        &edge.node.owner.login,
-                        request.first,
-                        None,
-                    ))
-                })
+            .map(|node| {
+                let owner_name = &node.owner.login;
+                match (node.owner.owner_type(), self.organization_traversal) {
+                    (OwnerType::Organization, OrganizationTraversal::Connection) => {
+                        Request::OrganizationRepositories(OrganizationRepositoriesRequest::new(
+                            owner_name,
+                            request.first,
+                            None,
+                        ))
+                    }
+                    (OwnerType::Organization, OrganizationTraversal::Search) => {
+                        Request::RepositoriesFromOrganization(
+                            RepositoriesFromOrganizationRequest::new(
+                                owner_name,
+                                request.first,
+                                None,
+                            ),
+                        )
+                    }
+                    (OwnerType::User, _) => Request::RepositoriesFromUser(
+                        RepositoriesFromUserRequest::new(owner_name, request.first, None),
+                    ),
+                }
             })
             .collect::<Vec<_>>();
-        if fetched_data.search.pageInfo.hasNextPage {
-            next_requests.push(Request::SearchOrganization(SearchOrganizationRequest::new(
-                &request.query,
-                request.first,
-                fetched_data.search.pageInfo.endCursor,
-            )));
+        let is_capped = request.after.is_none()
+            && fetched_data.search.repositoryCount > GITHUB_SEARCH_RESULTS_CAP;
+        let partitions = match is_capped {
+            true => request
+                .split_partition(Utc::now().date_naive())
+                .map_err(|e| FetcherError::Partition(e.to_string()))?
+                .or_else(|| {
+                    warn!("Search results are capped and can not be partitioned for request: {request}");
+                    None
+                }),
+            false => None,
+        };
+        match partitions {
+            Some(partitions) => {
+                next_requests.extend(partitions.into_iter().map(|partition| {
+                    Request::SearchOrganization(
+                        SearchOrganizationRequest::new(&request.query, request.first, None)
+                            .with_partition(partition),
+                    )
+                }));
+            }
+            None if fetched_data.search.pageInfo.hasNextPage => {
+                next_requests.push(Request::SearchOrganization(
+                    SearchOrganizationRequest::new(
+                        &request.query,
+                        request.first,
+                        fetched_data.search.pageInfo.endCursor,
+                    )
+                    .with_partition(request.partition.clone()),
+                ));
+            }
+            None => {}
         }
 
         Ok(Some((
-            Response::new(vec![], fetched_data.rateLimit.into()),
+            Response::new(vec![], fetched_data.rateLimit.into()).with_api_token(&api_token),
             next_requests,
         )))
     }
 
-    async fn fetch_repositories_from_organization(
+    /// Fetches a page of the repositories of an owner, along with the cursor of the next page if any.
+    async fn fetch_repositories_from_owner(
         &self,
-        request: &RepositoriesFromOrganizationRequest,
-    ) -> StdResult<Option<(Response, Vec<Request>)>> {
-        let fetched_data = self
-            .client
-            .query_with_vars_unwrap::<SearchQueryData, GraphQlSearchQuery>(
-                SEARCH_QUERY,
-                request.into(),
-            )
+        query: GraphQlSearchQuery,
+        owner_name: &str,
+        owner_type: OwnerType,
+    ) -> StdResult<Option<(Response, Option<String>)>> {
+        let (fetched_data, api_token) = self
+            .search(query.with_repository_fields(&self.repository_fields))
             .await
-            .map_err(|e| e.into())
-            .map_err(|e: FetcherError| anyhow!(e))?;
+            .map_err(|e| anyhow!(e))?;
         if fetched_data.search.edges.is_empty() {
             return Ok(None);
         }
 
         Ok(Some((
             Response::new(
-                fetched_data
-                    .search
-                    .edges
-                    .into_iter()
-                    .filter_map(|edge| {
-                        edge.map(|edge| {
-                            Repository::new(
-                                &edge.node.name,
+                self.build_repositories(
+                    edge_nodes(fetched_data.search.edges),
+                    owner_name,
+                    owner_type,
+                )
+                .await,
+                fetched_data.rateLimit.into(),
+            )
+            .with_api_token(&api_token),
+            fetched_data
+                .search
+                .pageInfo
+                .endCursor
+                .filter(|_| fetched_data.search.pageInfo.hasNextPage),
+        )))
+    }
+
+    async fn fetch_repositories_from_organization(
+        &self,
+        request: &RepositoriesFromOrganizationRequest,
+    ) -> StdResult<Option<(Response, Vec<Request>)>> {
+        let fetched = self
+            .fetch_repositories_from_owner(
+                request.into(),
+                &request.organization_name,
+                OwnerType::Organization,
+            )
+            .await?;
+
+        Ok(fetched.map(|(response, next_cursor)| {
+            (
+                response,
+                next_cursor
+                    .map(|next_cursor| {
+                        Request::RepositoriesFromOrganization(
+                            RepositoriesFromOrganizationRequest::new(
                                 &request.organization_name,
-                                edge.node.stargazerCount,
-                            )
-                        })
+                                request.first,
+                                Some(next_cursor),
+                            ),
+                        )
                     })
+                    .into_iter()
                     .collect(),
-                fetched_data.rateLimit.into(),
-            ),
-            if fetched_data.search.pageInfo.hasNextPage {
-                vec![Request::RepositoriesFromOrganization(
-                    RepositoriesFromOrganizationRequest::new(
-                        &request.organization_name,
-                        request.first,
-                        fetched_data.search.pageInfo.endCursor,
-                    ),
-                )]
-            } else {
-                vec![]
-            },
-        )))
+            )
+        }))
     }
-}
 
-#[async_trait::async_trait]
-impl RepositoryFetcher for GraphQlFetcher {
-    async fn fetch(&self, request: &Request) -> StdResult<Option<(Response, Vec<Request>)>> {
-        match request {
+    /// Fetches known repositories by their node identifiers, reporting the deleted or inaccessible ones.
+    async fn refresh_repositories(
+        &self,
+        request: &RefreshRepositoriesRequest,
+    ) -> StdResult<Option<(Response, Vec<Request>)>> {
+        let (fetched_data, api_token): (RefreshQueryData, _) = self
+            .query(
+                REFRESH_QUERY,
+                GraphQlRefreshQuery::from(request).with_repository_fields(&self.repository_fields),
+            )
+            .await
+            .map_err(|e| anyhow!(e))?;
+        let mut nodes = Vec::new();
+        let mut unreachable_node_ids = Vec::new();
+        for (node_id, node) in request.node_ids.iter().zip(fetched_data.nodes) {
+            match node.filter(|node| !node.is_null()) {
+                Some(node) => nodes.push(Some(node)),
+                None => unreachable_node_ids.push(node_id.to_owned()),
+            }
+        }
+        let observed_at = Utc::now();
+        let repositories = self
+            .parse_nodes(nodes)
+            .await
+            .into_iter()
+            .map(|node| {
+                Repository::new(&node.name, &node.owner.login, node.stargazerCount)
+                    .with_node_id(&node.id)
+                    .with_owner_type(node.owner.owner_type())
+                    .with_observed_at(observed_at)
+                    .with_metadata(node.into())
+            })
+            .collect();
+
+        Ok(Some((
+            Response::new(repositories, fetched_data.rateLimit.into())
+                .with_api_token(&api_token)
+                .with_unreachable_node_ids(unreachable_node_ids),
+            vec![],
+        )))
+    }
+
+    /// Retrieves the maximum number of organizations walked in a single query with pages of up to `first` repositories.
+    pub fn max_organizations_per_query(first: u16, repository_fields: &[RepositoryField]) -> usize {
+        let nodes_per_repository = match repository_fields.contains(&RepositoryField::Topics) {
+            true => 1 + REPOSITORY_TOPICS_FIRST,
+            false => 1,
+        };
+
+        (GITHUB_MAX_NODES_PER_BATCH / (u32::from(first.max(1)) * nodes_per_repository)).max(1)
+            as usize
+    }
+
+    /// Walks the repositories connections of the organizations with aliased queries, splitting the results back by request.
+    async fn fetch_organization_repositories(
+        &self,
+        requests: &[OrganizationRepositoriesRequest],
+    ) -> StdResult<Option<(Response, Vec<Request>)>> {
+        // The size of the batches is bounded by the crawler with `max_organizations_per_query`
+        let (fetched_data, api_token): (OrganizationsQueryData, _) = self
+            .query(
+                &organization_repositories_query(requests.len()),
+                GraphQlOrganizationsQuery::from(requests)
+                    .with_repository_fields(&self.repository_fields),
+            )
+            .await
+            .map_err(|e| anyhow!(e))?;
+        let mut organizations = fetched_data.organizations;
+        let mut repositories = Vec::new();
+        let mut next_requests = Vec::new();
+        for (index, request) in requests.iter().enumerate() {
+            let Some(organization) = organizations.remove(&format!("o{index}")).flatten() else {
+                warn!("Organization not found for request: {request}");
+                continue;
+            };
+            let connection = organization.repositories;
+            repositories.extend(
+                self.build_repositories(
+                    connection.nodes,
+                    &request.organization_name,
+                    OwnerType::Organization,
+                )
+                .await,
+            );
+            if connection.pageInfo.hasNextPage
+                && let Some(end_cursor) = connection.pageInfo.endCursor
+            {
+                next_requests.push(Request::OrganizationRepositories(
+                    OrganizationRepositoriesRequest::new(
+                        &request.organization_name,
+                        request.first,
+                        Some(end_cursor),
+                    ),
+                ));
+            }
+        }
+        if repositories.is_empty() && next_requests.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some((
+            Response::new(repositories, fetched_data.rateLimit.into()).with_api_token(&api_token),
+            next_requests,
+        )))
+    }
+
+    async fn fetch_repositories_from_user(
+        &self,
+        request: &RepositoriesFromUserRequest,
+    ) -> StdResult<Option<(Response, Vec<Request>)>> {
+        let fetched = self
+            .fetch_repositories_from_owner(request.into(), &request.user_name, OwnerType::User)
+            .await?;
+
+        Ok(fetched.map(|(response, next_cursor)| {
+            (
+                response,
+                next_cursor
+                    .map(|next_cursor| {
+                        Request::RepositoriesFromUser(RepositoriesFromUserRequest::new(
+                            &request.user_name,
+                            request.first,
+                            Some(next_cursor),
+                        ))
+                    })
+                    .into_iter()
+                    .collect(),
+            )
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl RepositoryFetcher for GraphQlFetcher {
+    async fn fetch(&self, request: &Request) -> StdResult<Option<(Response, Vec<Request>)>> {
+        match request {
             Request::SearchOrganization(request) => self.fetch_organizations(request).await,
             Request::RepositoriesFromOrganization(request) => {
                 self.fetch_repositories_from_organization(request).await
             }
+            Request::RepositoriesFromUser(request) => {
+                self.fetch_repositories_from_user(request).await
+            }
+            Request::OrganizationRepositories(request) => {
+                self.fetch_organization_repositories(std::slice::from_ref(request))
+                    .await
+            }
+            Request::OrganizationRepositoriesBatch(requests) => {
+                self.fetch_organization_repositories(requests).await
+            }
+            Request::RefreshRepositories(request) => self.refresh_repositories(request).await,
         }
     }
 }
@@ -302,9 +1163,11 @@ mod tests {
         json!({
             "data": {
                 "search": {
+                    "repositoryCount": 2,
                     "edges": [
                         {
                             "node": {
+                                "id": "R_1",
                                 "name": "repository-1",
                                 "owner": {
                                     "login": "org-1"
@@ -315,6 +1178,7 @@ mod tests {
                         null,
                         {
                             "node": {
+                                "id": "R_2",
                                 "name": "repository-2",
                                 "owner": {
                                     "login": "org-1"
@@ -357,15 +1221,18 @@ mod tests {
             .unwrap();
 
         mock.assert();
-        assert_eq!(Response::new(vec![], FetcherRateLimit::dummy()), response);
+        assert_eq!(
+            Response::new(vec![], FetcherRateLimit::dummy()).with_api_token("token-1"),
+            response
+        );
         assert_eq!(
             vec![
-                Request::RepositoriesFromOrganization(RepositoriesFromOrganizationRequest::new(
+                Request::OrganizationRepositories(OrganizationRepositoriesRequest::new(
                     "org-1", 10, None,
-                ),),
-                Request::RepositoriesFromOrganization(RepositoriesFromOrganizationRequest::new(
+                )),
+                Request::OrganizationRepositories(OrganizationRepositoriesRequest::new(
                     "org-1", 10, None,
-                ),),
+                )),
                 Request::SearchOrganization(SearchOrganizationRequest::new(
                     "stars:>100",
                     10,
@@ -376,6 +1243,581 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_fetch_organizations_of_users_with_search_traversal() {
+        let server = setup_mock_server();
+        let mock = server.mock(|when, then| {
+            let mut json_value = mock_json_value();
+            json_value["data"]["search"]["edges"][0]["node"]["owner"] =
+                json!({ "login": "user-1", "__typename": "User" });
+            json_value["data"]["search"]["edges"][2]["node"]["owner"] =
+                json!({ "login": "org-1", "__typename": "Organization" });
+            json_value["data"]["search"]["pageInfo"]["hasNextPage"] = json!(false);
+            when.method("POST").path("/");
+            then.status(200)
+                .header("Content-Type", "application/json")
+                .json_body(json_value);
+        });
+        let fetcher = GraphQlFetcher::try_new(&server.url("/"))
+            .unwrap()
+            .with_organization_traversal(OrganizationTraversal::Search);
+        let request = SearchOrganizationRequest::new("stars:>100", 10, None);
+
+        let (_response, next_requests) = fetcher
+            .fetch_organizations(&request)
+            .await
+            .unwrap()
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(
+            vec![
+                Request::RepositoriesFromUser(
+                    RepositoriesFromUserRequest::new("user-1", 10, None,)
+                ),
+                Request::RepositoriesFromOrganization(RepositoriesFromOrganizationRequest::new(
+                    "org-1", 10, None,
+                )),
+            ],
+            next_requests
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repositories_from_user() {
+        let server = setup_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method("POST")
+                .path("/")
+                .json_body_partial(r#"{"variables": {"query": "user:user-1 stars:>0"}}"#);
+            then.status(200)
+                .header("Content-Type", "application/json")
+                .json_body(mock_json_value());
+        });
+        let fetcher = GraphQlFetcher::try_new(&server.url("/")).unwrap();
+        let request =
+            Request::RepositoriesFromUser(RepositoriesFromUserRequest::new("user-1", 10, None));
+
+        let (response, next_requests) = fetcher.fetch(&request).await.unwrap().unwrap();
+
+        mock.assert();
+        assert!(response.repositories().iter().all(|repository| {
+            repository.owner_type() == Some(OwnerType::User)
+                && repository.organization_name().as_str() == "user-1"
+        }));
+        assert_eq!(
+            vec![Request::RepositoriesFromUser(
+                RepositoriesFromUserRequest::new("user-1", 10, Some("cursor123".to_string()),)
+            )],
+            next_requests
+        );
+    }
+
+    fn mock_organization_json_value() -> serde_json::Value {
+        json!({
+            "data": {
+                "o0": {
+                    "repositories": {
+                        "nodes": [
+                            {
+                                "id": "R_1",
+                                "name": "repository-1",
+                                "owner": {
+                                    "login": "org-1",
+                                    "__typename": "Organization"
+                                },
+                                "stargazerCount": 100
+                            },
+                            {
+                                "id": "R_2",
+                                "name": "repository-2",
+                                "owner": {
+                                    "login": "org-1",
+                                    "__typename": "Organization"
+                                },
+                                "stargazerCount": 0
+                            }
+                        ],
+                        "pageInfo": {
+                            "endCursor": "cursor123",
+                            "hasNextPage": true
+                        }
+                    }
+                },
+                "rateLimit": {
+                    "limit": 5000,
+                    "cost": 1,
+                    "remaining": 4999,
+                    "resetAt": "2025-01-01T00:00:00Z"
+                }
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_fetch_organization_repositories() {
+        let server = setup_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method("POST")
+                .path("/")
+                .json_body_partial(r#"{"variables": {"login0": "org-1", "first0": 10}}"#);
+            then.status(200)
+                .header("Content-Type", "application/json")
+                .json_body(mock_organization_json_value());
+        });
+        let fetcher = GraphQlFetcher::try_new(&server.url("/")).unwrap();
+        let request = Request::OrganizationRepositories(OrganizationRepositoriesRequest::new(
+            "org-1", 10, None,
+        ));
+
+        let (response, next_requests) = fetcher.fetch(&request).await.unwrap().unwrap();
+
+        mock.assert();
+        let observed_at = response.repositories()[0].observed_at().unwrap();
+        assert_eq!(
+            Response::new(
+                vec![
+                    Repository::new("repository-1", "org-1", 100)
+                        .with_node_id("R_1")
+                        .with_owner_type(OwnerType::Organization)
+                        .with_observed_at(observed_at),
+                    Repository::new("repository-2", "org-1", 0)
+                        .with_node_id("R_2")
+                        .with_owner_type(OwnerType::Organization)
+                        .with_observed_at(observed_at)
+                ],
+                FetcherRateLimit::dummy()
+            )
+            .with_api_token("token-1"),
+            response
+        );
+        assert_eq!(
+            vec![Request::OrganizationRepositories(
+                OrganizationRepositoriesRequest::new("org-1", 10, Some("cursor123".to_string()))
+            )],
+            next_requests
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_organization_repositories_of_missing_organization() {
+        let server = setup_mock_server();
+        let mock = server.mock(|when, then| {
+            let mut json_value = mock_organization_json_value();
+            json_value["data"]["o0"] = json!(null);
+            json_value["errors"] = json!([{
+                "type": "NOT_FOUND",
+                "path": ["o0"],
+                "message": "Could not resolve to an Organization with the login of 'org-1'."
+            }]);
+            when.method("POST").path("/");
+            then.status(200)
+                .header("Content-Type", "application/json")
+                .json_body(json_value);
+        });
+        let state = Arc::new(CrawlerState::default());
+        let fetcher = GraphQlFetcher::try_new(&server.url("/"))
+            .unwrap()
+            .with_state(state.clone());
+        let request = Request::OrganizationRepositories(OrganizationRepositoriesRequest::new(
+            "org-1", 10, None,
+        ));
+
+        let fetched = fetcher.fetch(&request).await.unwrap();
+
+        mock.assert();
+        assert!(fetched.is_none());
+        assert_eq!(1, state.get_total_node_errors().await);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_organization_repositories_batch() {
+        let server = setup_mock_server();
+        let mock = server.mock(|when, then| {
+            let mut json_value = mock_organization_json_value();
+            json_value["data"]["o1"] = json!(null);
+            when.method("POST").path("/").json_body_partial(
+                r#"{"variables": {"login0": "org-1", "after0": null, "login1": "org-2", "after1": "cursor-2"}}"#,
+            );
+            then.status(200)
+                .header("Content-Type", "application/json")
+                .json_body(json_value);
+        });
+        let fetcher = GraphQlFetcher::try_new(&server.url("/")).unwrap();
+        let request = Request::OrganizationRepositoriesBatch(vec![
+            OrganizationRepositoriesRequest::new("org-1", 10, None),
+            OrganizationRepositoriesRequest::new("org-2", 10, Some("cursor-2".to_string())),
+        ]);
+
+        let (response, next_requests) = fetcher.fetch(&request).await.unwrap().unwrap();
+
+        mock.assert();
+        assert_eq!(2, response.repositories().len());
+        assert_eq!(
+            vec![Request::OrganizationRepositories(
+                OrganizationRepositoriesRequest::new("org-1", 10, Some("cursor123".to_string()))
+            )],
+            next_requests
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_repositories() {
+        let server = setup_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method("POST")
+                .path("/")
+                .json_body_partial(r#"{"variables": {"ids": ["R_1", "R_2"]}}"#);
+            then.status(200)
+                .header("Content-Type", "application/json")
+                .json_body(json!({
+                    "data": {
+                        "nodes": [
+                            {
+                                "id": "R_1",
+                                "name": "repository-renamed",
+                                "owner": {
+                                    "login": "user-1",
+                                    "__typename": "User"
+                                },
+                                "stargazerCount": 150
+                            },
+                            null
+                        ],
+                        "rateLimit": {
+                            "limit": 5000,
+                            "cost": 1,
+                            "remaining": 4999,
+                            "resetAt": "2025-01-01T00:00:00Z"
+                        }
+                    },
+                    "errors": [{
+                        "type": "NOT_FOUND",
+                        "path": ["nodes", 1],
+                        "message": "Could not resolve to a node with the global id of 'R_2'"
+                    }]
+                }));
+        });
+        let fetcher = GraphQlFetcher::try_new(&server.url("/")).unwrap();
+        let request = Request::RefreshRepositories(RefreshRepositoriesRequest::new(vec![
+            "R_1".to_string(),
+            "R_2".to_string(),
+        ]));
+
+        let (response, next_requests) = fetcher.fetch(&request).await.unwrap().unwrap();
+
+        mock.assert();
+        let observed_at = response.repositories()[0].observed_at().unwrap();
+        assert_eq!(
+            vec![
+                Repository::new("repository-renamed", "user-1", 150)
+                    .with_node_id("R_1")
+                    .with_owner_type(OwnerType::User)
+                    .with_observed_at(observed_at)
+            ],
+            response.repositories()
+        );
+        assert_eq!(vec!["R_2".to_string()], response.unreachable_node_ids());
+        assert!(next_requests.is_empty());
+    }
+
+    #[test]
+    fn test_max_organizations_per_query() {
+        assert_eq!(100, GraphQlFetcher::max_organizations_per_query(100, &[]));
+        assert_eq!(
+            4,
+            GraphQlFetcher::max_organizations_per_query(100, &[RepositoryField::Topics])
+        );
+        assert_eq!(
+            47,
+            GraphQlFetcher::max_organizations_per_query(10, &[RepositoryField::Topics])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_organizations_skips_unparsable_response() {
+        let server = setup_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method("POST").path("/");
+            then.status(200)
+                .header("Content-Type", "application/json")
+                .body("not json");
+        });
+        let state = Arc::new(CrawlerState::default());
+        let fetcher = GraphQlFetcher::try_new(&server.url("/"))
+            .unwrap()
+            .with_state(state.clone());
+        let request = SearchOrganizationRequest::new("stars:>100", 10, None);
+
+        let fetched = fetcher.fetch_organizations(&request).await.unwrap();
+
+        mock.assert();
+        assert!(fetched.is_none());
+        assert_eq!(1, state.get_total_parse_errors().await);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repositories_from_organization_keeps_partial_response() {
+        let server = setup_mock_server();
+        let mock = server.mock(|when, then| {
+            let mut json_value = mock_json_value();
+            json_value["data"]["search"]["edges"][1] = json!({ "node": null });
+            json_value["data"]["search"]["edges"][2]["node"]["stargazerCount"] = json!("many");
+            json_value["errors"] = json!([{
+                "type": "FORBIDDEN",
+                "path": ["search", "edges", 1, "node"],
+                "message": "Resource protected by organization SAML enforcement."
+            }]);
+            when.method("POST").path("/");
+            then.status(200)
+                .header("Content-Type", "application/json")
+                .json_body(json_value);
+        });
+        let state = Arc::new(CrawlerState::default());
+        let fetcher = GraphQlFetcher::try_new(&server.url("/"))
+            .unwrap()
+            .with_state(state.clone());
+        let request = RepositoriesFromOrganizationRequest::new("org-1", 10, None);
+
+        let (response, next_requests) = fetcher
+            .fetch_repositories_from_organization(&request)
+            .await
+            .unwrap()
+            .unwrap();
+
+        mock.assert();
+        let observed_at = response.repositories()[0].observed_at().unwrap();
+        assert_eq!(
+            &[Repository::new("repository-1", "org-1", 100)
+                .with_node_id("R_1")
+                .with_owner_type(OwnerType::Organization)
+                .with_observed_at(observed_at)],
+            response.repositories()
+        );
+        assert_eq!(
+            vec![Request::RepositoriesFromOrganization(
+                RepositoriesFromOrganizationRequest::new(
+                    "org-1",
+                    10,
+                    Some("cursor123".to_string()),
+                )
+            )],
+            next_requests
+        );
+        assert_eq!(1, state.get_total_node_errors().await);
+        assert_eq!(1, state.get_total_parse_errors().await);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_organizations_with_token_with_most_remaining_budget() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("POST")
+                .path("/")
+                .header("Authorization", "Bearer secret-2");
+            then.status(200)
+                .header("Content-Type", "application/json")
+                .json_body(mock_json_value());
+        });
+        let token_pool = Arc::new(
+            TokenPool::try_from_secrets(vec!["secret-1".to_string(), "secret-2".to_string()])
+                .unwrap(),
+        );
+        token_pool
+            .update(
+                0,
+                FetcherRateLimit {
+                    remaining: 0,
+                    reset_at: "2100-01-01T00:00:00Z".to_string(),
+                    ..FetcherRateLimit::dummy()
+                },
+            )
+            .await;
+        let fetcher = GraphQlFetcher::new(&server.url("/"), token_pool.clone());
+        let request = SearchOrganizationRequest::new("stars:>100", 10, None);
+
+        let (response, _next_requests) = fetcher
+            .fetch_organizations(&request)
+            .await
+            .unwrap()
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(Some("token-2"), response.api_token());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_organizations_surfaces_secondary_rate_limit() {
+        let server = setup_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method("POST").path("/");
+            then.status(403)
+                .header("Retry-After", "30")
+                .header("x-ratelimit-remaining", "4000")
+                .body("You have exceeded a secondary rate limit");
+        });
+        let token_pool = Arc::new(TokenPool::try_from_env().unwrap());
+        let fetcher = GraphQlFetcher::new(&server.url("/"), token_pool.clone());
+        let request = SearchOrganizationRequest::new("stars:>100", 10, None);
+
+        let error = fetcher.fetch_organizations(&request).await.unwrap_err();
+
+        mock.assert();
+        match error.downcast_ref::<FetcherError>() {
+            Some(FetcherError::RateLimited {
+                status, headers, ..
+            }) => {
+                assert_eq!(403, *status);
+                assert_eq!(Some(Duration::from_secs(30)), headers.retry_after);
+                assert_eq!(Some(4000), headers.remaining);
+            }
+            _ => panic!("Unexpected error: {error:?}"),
+        }
+        assert!(token_pool.throttle_delay(Utc::now()).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_organizations_throttles_on_graphql_rate_limit() {
+        let server = setup_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method("POST").path("/");
+            then.status(200)
+                .header("Content-Type", "application/json")
+                .json_body(json!({
+                    "errors": [{
+                        "type": "RATE_LIMITED",
+                        "message": "API rate limit exceeded"
+                    }]
+                }));
+        });
+        let token_pool = Arc::new(TokenPool::try_from_env().unwrap());
+        let fetcher = GraphQlFetcher::new(&server.url("/"), token_pool.clone());
+        let request = SearchOrganizationRequest::new("stars:>100", 10, None);
+
+        let error = fetcher.fetch_organizations(&request).await.unwrap_err();
+
+        mock.assert();
+        assert!(matches!(
+            error.downcast_ref::<FetcherError>(),
+            Some(FetcherError::RateLimited { status: 200, .. })
+        ));
+        assert!(token_pool.throttle_delay(Utc::now()).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_organizations_partitions_search_above_cap() {
+        let server = setup_mock_server();
+        let mock = server.mock(|when, then| {
+            let mut json_value = mock_json_value();
+            json_value["data"]["search"]["repositoryCount"] = json!(GITHUB_SEARCH_RESULTS_CAP + 1);
+            when.method("POST").path("/");
+            then.status(200)
+                .header("Content-Type", "application/json")
+                .json_body(json_value);
+        });
+        let fetcher = GraphQlFetcher::try_new(&server.url("/")).unwrap();
+        let request = SearchOrganizationRequest::new("stars:>100", 10, None);
+
+        let (_response, next_requests) = fetcher
+            .fetch_organizations(&request)
+            .await
+            .unwrap()
+            .unwrap();
+
+        mock.assert();
+        let expected_partitions = request
+            .split_partition(Utc::now().date_naive())
+            .unwrap()
+            .unwrap();
+        assert_eq!(Some((101, None)), expected_partitions[0].stars);
+        assert_eq!(
+            vec![
+                Request::OrganizationRepositories(OrganizationRepositoriesRequest::new(
+                    "org-1", 10, None,
+                )),
+                Request::OrganizationRepositories(OrganizationRepositoriesRequest::new(
+                    "org-1", 10, None,
+                )),
+                Request::SearchOrganization(
+                    SearchOrganizationRequest::new("stars:>100", 10, None)
+                        .with_partition(expected_partitions[0].clone()),
+                ),
+                Request::SearchOrganization(
+                    SearchOrganizationRequest::new("stars:>100", 10, None)
+                        .with_partition(expected_partitions[1].clone()),
+                ),
+            ],
+            next_requests
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repositories_from_organization_with_metadata() {
+        let server = setup_mock_server();
+        let mock = server.mock(|when, then| {
+            let mut json_value = mock_json_value();
+            json_value["data"]["search"]["edges"] = json!([{
+                "node": {
+                    "id": "R_1",
+                    "name": "repository-1",
+                    "owner": {
+                        "login": "org-1"
+                    },
+                    "stargazerCount": 100,
+                    "databaseId": 123,
+                    "primaryLanguage": {
+                        "name": "Rust"
+                    },
+                    "isArchived": false,
+                    "createdAt": "2020-01-01T00:00:00Z",
+                    "licenseInfo": null,
+                    "repositoryTopics": {
+                        "nodes": [
+                            { "topic": { "name": "crawler" } },
+                            { "topic": { "name": "github" } }
+                        ]
+                    }
+                }
+            }]);
+            when.method("POST").path("/").json_body_partial(
+                r#"{"variables": {"topicsFirst": 20, "withDatabaseId": true, "withTopics": true}}"#,
+            );
+            then.status(200)
+                .header("Content-Type", "application/json")
+                .json_body(json_value);
+        });
+        let fetcher = GraphQlFetcher::try_new(&server.url("/"))
+            .unwrap()
+            .with_repository_fields(&[
+                RepositoryField::DatabaseId,
+                RepositoryField::PrimaryLanguage,
+                RepositoryField::IsArchived,
+                RepositoryField::CreatedAt,
+                RepositoryField::License,
+                RepositoryField::Topics,
+            ]);
+        let request = RepositoriesFromOrganizationRequest::new("org-1", 10, None);
+
+        let (response, _next_requests) = fetcher
+            .fetch_repositories_from_organization(&request)
+            .await
+            .unwrap()
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(
+            &RepositoryMetadata {
+                database_id: Some(123),
+                primary_language: Some("Rust".to_string()),
+                is_archived: Some(false),
+                created_at: Some("2020-01-01T00:00:00Z".parse().unwrap()),
+                topics: Some(vec!["crawler".to_string(), "github".to_string()]),
+                ..RepositoryMetadata::default()
+            },
+            response.repositories()[0].metadata()
+        );
+    }
+
     #[tokio::test]
     async fn test_fetch_repositories_from_organization() {
         let server = setup_mock_server();
@@ -395,14 +1837,22 @@ mod tests {
             .unwrap();
 
         mock.assert();
+        let observed_at = response.repositories()[0].observed_at().unwrap();
         assert_eq!(
             Response::new(
                 vec![
-                    Repository::new("repository-1", "org-1", 100),
+                    Repository::new("repository-1", "org-1", 100)
+                        .with_node_id("R_1")
+                        .with_owner_type(OwnerType::Organization)
+                        .with_observed_at(observed_at),
                     Repository::new("repository-2", "org-1", 200)
+                        .with_node_id("R_2")
+                        .with_owner_type(OwnerType::Organization)
+                        .with_observed_at(observed_at)
                 ],
                 FetcherRateLimit::dummy()
-            ),
+            )
+            .with_api_token("token-1"),
             response
         );
         assert_eq!(
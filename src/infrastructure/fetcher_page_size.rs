@@ -0,0 +1,395 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use log::info;
+use tokio::sync::RwLock;
+
+use crate::{CrawlerState, FetcherError, RepositoryFetcher, Request, Response, StdResult};
+
+/// The maximum number of results per page accepted by the GitHub GraphQL API.
+pub const GITHUB_MAX_PAGE_SIZE: u16 = 100;
+
+/// The weight of the latest request in the moving averages of the cost and latency.
+const PAGE_SIZE_SMOOTHING_FACTOR: f64 = 0.5;
+
+/// The rate limit cost per returned repository above which a page is not grown.
+///
+/// GitHub charges about one point per 100 nodes with a minimum of one point per query, so the cost per repository
+/// only rises for pages that are not filled, which a larger page size does not help.
+const PAGE_SIZE_MAX_COST_PER_REPOSITORY: f64 = 0.5;
+
+/// The latency above which a request is considered slow, well below the timeouts of the GitHub API.
+const PAGE_SIZE_TARGET_LATENCY: Duration = Duration::from_secs(5);
+
+/// The observed cost and latency of the requests of a kind, with their adapted page size.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct PageSizeStats {
+    /// The page size of the requests.
+    first: u16,
+
+    /// The number of requests observed since the last adjustment of the page size.
+    total_observations: u32,
+
+    /// The moving average of the rate limit cost per returned repository of the requests.
+    average_cost_per_repository: f64,
+
+    /// The moving average of the latency of the requests, in seconds.
+    average_latency: f64,
+}
+
+impl PageSizeStats {
+    /// Records the cost per returned repository and the latency of a request in the moving averages.
+    fn observe(&mut self, cost: i32, total_repositories: usize, latency: Duration) {
+        let smoothing_factor = match self.total_observations {
+            0 => 1.0,
+            _ => PAGE_SIZE_SMOOTHING_FACTOR,
+        };
+        let cost_per_repository = cost.max(0) as f64 / total_repositories.max(1) as f64;
+        self.average_cost_per_repository +=
+            smoothing_factor * (cost_per_repository - self.average_cost_per_repository);
+        self.average_latency += smoothing_factor * (latency.as_secs_f64() - self.average_latency);
+        self.total_observations += 1;
+    }
+}
+
+/// A fetcher that adapts the page size of the requests to their observed cost and latency.
+///
+/// The page size of a kind of requests shrinks after timeouts and slow responses, and grows back while the
+/// responses are fast and cheap per returned repository, within the given bounds. The total cost of a query is
+/// not a signal, since a smaller page spends the same or more points over more queries.
+pub struct FetcherPageSizeAdapter {
+    /// The fetcher whose requests are adapted.
+    fetcher: Arc<dyn RepositoryFetcher>,
+
+    /// The state of the crawler
+    state: Arc<CrawlerState>,
+
+    /// The minimum page size of the requests.
+    min_first: u16,
+
+    /// The maximum page size of the requests.
+    max_first: u16,
+
+    /// The observed cost and latency of the requests, by request kind.
+    stats: RwLock<HashMap<&'static str, PageSizeStats>>,
+}
+
+impl FetcherPageSizeAdapter {
+    /// Creates a new `FetcherPageSizeAdapter` instance with the given page size bounds.
+    pub fn new(
+        fetcher: Arc<dyn RepositoryFetcher>,
+        min_first: u16,
+        max_first: u16,
+        state: Arc<CrawlerState>,
+    ) -> Self {
+        Self {
+            fetcher,
+            state,
+            min_first: min_first.clamp(1, max_first.max(1)),
+            max_first: max_first.max(1),
+            stats: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Retrieves the page size of the request, starting from its own page size for a new kind of requests.
+    async fn page_size(&self, request: &Request) -> u16 {
+        let mut stats = self.stats.write().await;
+        stats
+            .entry(request.kind())
+            .or_insert_with(|| PageSizeStats {
+                first: request.get_first().clamp(self.min_first, self.max_first),
+                ..PageSizeStats::default()
+            })
+            .first
+    }
+
+    /// Records the cost and latency of a successful request, and grows or shrinks the page size of its kind.
+    async fn observe(
+        &self,
+        request_kind: &'static str,
+        cost: i32,
+        total_repositories: usize,
+        latency: Duration,
+    ) {
+        let mut stats = self.stats.write().await;
+        let Some(stats) = stats.get_mut(request_kind) else {
+            return;
+        };
+        stats.observe(cost, total_repositories, latency);
+        let target_latency = PAGE_SIZE_TARGET_LATENCY.as_secs_f64();
+        if stats.average_latency > target_latency {
+            let first = stats.first - stats.first / 4;
+            self.adjust(request_kind, stats, first, "slow responses")
+                .await;
+        } else if stats.average_latency < target_latency / 2.0
+            && stats.average_cost_per_repository <= PAGE_SIZE_MAX_COST_PER_REPOSITORY
+        {
+            let first = stats.first.saturating_add((stats.first / 2).max(1));
+            self.adjust(request_kind, stats, first, "cheap and fast responses")
+                .await;
+        }
+    }
+
+    /// Sets the page size of a kind of requests within the bounds, recording the decision when it changes.
+    async fn adjust(
+        &self,
+        request_kind: &'static str,
+        stats: &mut PageSizeStats,
+        first: u16,
+        reason: &str,
+    ) {
+        let first = first.clamp(self.min_first, self.max_first);
+        if first == stats.first {
+            return;
+        }
+        info!(
+            "Adapted page size of {request_kind} requests from {} to {first} after {reason} (average cost per repository {:.2}, average latency {:.1}s)",
+            stats.first, stats.average_cost_per_repository, stats.average_latency
+        );
+        *stats = PageSizeStats {
+            first,
+            ..PageSizeStats::default()
+        };
+        self.state
+            .record_page_size_adjustment(request_kind, first)
+            .await;
+    }
+
+    /// Halves the page size of a kind of requests after a timeout.
+    async fn shrink_after_timeout(&self, request_kind: &'static str) {
+        let mut stats = self.stats.write().await;
+        if let Some(stats) = stats.get_mut(request_kind) {
+            let first = stats.first / 2;
+            self.adjust(request_kind, stats, first, "a timeout").await;
+        }
+    }
+}
+
+/// Is the error a timeout of the API, either of the connection or of the query on the server side
+fn is_timeout(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<FetcherError>(),
+        Some(
+            FetcherError::Network(_)
+                | FetcherError::Server {
+                    status: 502 | 504,
+                    ..
+                }
+        )
+    )
+}
+
+#[async_trait::async_trait]
+impl RepositoryFetcher for FetcherPageSizeAdapter {
+    /// Fetches the request with the page size adapted for its kind.
+    async fn fetch(&self, request: &Request) -> StdResult<Option<(Response, Vec<Request>)>> {
+        if let Request::RefreshRepositories(_) = request {
+            return self.fetcher.fetch(request).await;
+        }
+        let request_kind = request.kind();
+        let request = request.clone().with_first(self.page_size(request).await);
+        let started_at = Instant::now();
+        match self.fetcher.fetch(&request).await {
+            Ok(fetched) => {
+                if let Some((response, _)) = &fetched {
+                    self.observe(
+                        request_kind,
+                        response.rate_limit().cost,
+                        response.repositories().len(),
+                        started_at.elapsed(),
+                    )
+                    .await;
+                }
+                Ok(fetched)
+            }
+            Err(e) => {
+                if is_timeout(&e) {
+                    self.shrink_after_timeout(request_kind).await;
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use anyhow::anyhow;
+
+    use crate::{
+        FetcherRateLimit, MockRepositoryFetcher, OrganizationRepositoriesRequest, Repository,
+    };
+
+    use super::*;
+
+    /// Mocks a fetcher answering with the given costs, filling the pages of the requests unless a number of
+    /// repositories is given.
+    fn mock_fetcher(
+        results: Vec<StdResult<(i32, Option<usize>)>>,
+        firsts: Arc<Mutex<Vec<u16>>>,
+    ) -> MockRepositoryFetcher {
+        let results = Mutex::new(results.into_iter());
+        let mut mock_fetcher = MockRepositoryFetcher::new();
+        mock_fetcher.expect_fetch().returning(move |request| {
+            firsts.lock().unwrap().push(request.get_first());
+            let (cost, total_repositories) = results.lock().unwrap().next().unwrap()?;
+            let total_repositories = total_repositories
+                .unwrap_or(request.get_first() as usize * request.unbatch().len());
+            Ok(Some((
+                Response::new(
+                    (0..total_repositories)
+                        .map(|index| Repository::new(&format!("repository-{index}"), "org-1", 0))
+                        .collect(),
+                    FetcherRateLimit {
+                        cost,
+                        ..FetcherRateLimit::dummy()
+                    },
+                ),
+                vec![],
+            )))
+        });
+
+        mock_fetcher
+    }
+
+    #[tokio::test]
+    async fn fetch_grows_page_size_of_cheap_requests() {
+        let state = Arc::new(CrawlerState::default());
+        let firsts = Arc::new(Mutex::new(vec![]));
+        let fetcher = FetcherPageSizeAdapter::new(
+            Arc::new(mock_fetcher(
+                vec![Ok((1, None)), Ok((1, None)), Ok((1, None))],
+                firsts.clone(),
+            )),
+            10,
+            100,
+            state.clone(),
+        );
+        let request = Request::dummy_search_organization().with_first(40);
+
+        for _ in 0..3 {
+            fetcher.fetch(&request).await.unwrap();
+        }
+
+        assert_eq!(vec![40, 60, 90], *firsts.lock().unwrap());
+        assert_eq!(
+            Some(&100),
+            state.get_page_sizes().await.get("search_organization")
+        );
+        assert_eq!(3, state.get_total_page_size_adjustments().await);
+    }
+
+    #[tokio::test]
+    async fn fetch_keeps_page_size_of_batched_requests_with_high_cost() {
+        let state = Arc::new(CrawlerState::default());
+        let firsts = Arc::new(Mutex::new(vec![]));
+        let fetcher = FetcherPageSizeAdapter::new(
+            Arc::new(mock_fetcher(
+                vec![Ok((10, None)), Ok((15, None)), Ok((20, None))],
+                firsts.clone(),
+            )),
+            10,
+            100,
+            state.clone(),
+        );
+        let request = Request::OrganizationRepositoriesBatch(
+            (0..10)
+                .map(|index| {
+                    OrganizationRepositoriesRequest::new(&format!("org-{index}"), 40, None)
+                })
+                .collect(),
+        );
+
+        for _ in 0..3 {
+            fetcher.fetch(&request).await.unwrap();
+        }
+
+        assert_eq!(vec![40, 60, 90], *firsts.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn fetch_does_not_grow_page_size_of_pages_not_filled() {
+        let state = Arc::new(CrawlerState::default());
+        let firsts = Arc::new(Mutex::new(vec![]));
+        let fetcher = FetcherPageSizeAdapter::new(
+            Arc::new(mock_fetcher(
+                vec![Ok((1, Some(1))), Ok((1, Some(1)))],
+                firsts.clone(),
+            )),
+            10,
+            100,
+            state.clone(),
+        );
+        let request = Request::dummy_search_organization().with_first(40);
+
+        for _ in 0..2 {
+            fetcher.fetch(&request).await.unwrap();
+        }
+
+        assert_eq!(vec![40, 40], *firsts.lock().unwrap());
+        assert_eq!(0, state.get_total_page_size_adjustments().await);
+    }
+
+    #[tokio::test]
+    async fn fetch_shrinks_page_size_after_timeouts() {
+        let state = Arc::new(CrawlerState::default());
+        let firsts = Arc::new(Mutex::new(vec![]));
+        let fetcher = FetcherPageSizeAdapter::new(
+            Arc::new(mock_fetcher(
+                vec![
+                    Err(anyhow!(FetcherError::Server {
+                        status: 502,
+                        message: "Bad Gateway".to_string(),
+                    })),
+                    Ok((1, None)),
+                    Err(anyhow!(FetcherError::Network("timeout".to_string()))),
+                    Ok((1, None)),
+                ],
+                firsts.clone(),
+            )),
+            10,
+            100,
+            state.clone(),
+        );
+        let request = Request::dummy_search_organization().with_first(100);
+
+        for _ in 0..4 {
+            let _ = fetcher.fetch(&request).await;
+        }
+
+        assert_eq!(vec![100, 50, 75, 37], *firsts.lock().unwrap());
+        assert_eq!(4, state.get_total_page_size_adjustments().await);
+    }
+
+    #[tokio::test]
+    async fn fetch_keeps_page_size_within_bounds() {
+        let state = Arc::new(CrawlerState::default());
+        let firsts = Arc::new(Mutex::new(vec![]));
+        let fetcher = FetcherPageSizeAdapter::new(
+            Arc::new(mock_fetcher(
+                vec![
+                    Err(anyhow!(FetcherError::Network("timeout".to_string()))),
+                    Err(anyhow!(FetcherError::Network("timeout".to_string()))),
+                    Err(anyhow!(FetcherError::Auth("Bad credentials".to_string()))),
+                ],
+                firsts.clone(),
+            )),
+            10,
+            100,
+            state.clone(),
+        );
+        let request = Request::dummy_search_organization().with_first(15);
+
+        for _ in 0..3 {
+            let _ = fetcher.fetch(&request).await;
+        }
+
+        assert_eq!(vec![15, 10, 10], *firsts.lock().unwrap());
+        assert_eq!(1, state.get_total_page_size_adjustments().await);
+    }
+}
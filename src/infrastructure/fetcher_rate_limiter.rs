@@ -50,7 +50,7 @@ mod tests {
         let now = Utc::now();
         let reset_at = now + chrono::Duration::seconds(60);
         let fetcher_rate_limit_enforcer = FetcherRateLimitEnforcer::new(Arc::new({
-            let reset_at_clone = reset_at.clone();
+            let reset_at_clone = reset_at;
             let mut mock_fetcher = MockRepositoryFetcher::new();
             mock_fetcher
                 .expect_fetch()
@@ -84,7 +84,7 @@ mod tests {
         let now = Utc::now();
         let reset_at = now + chrono::Duration::seconds(1);
         let fetcher_rate_limit_enforcer = FetcherRateLimitEnforcer::new(Arc::new({
-            let reset_at_clone = reset_at.clone();
+            let reset_at_clone = reset_at;
             let mut mock_fetcher = MockRepositoryFetcher::new();
             mock_fetcher
                 .expect_fetch()
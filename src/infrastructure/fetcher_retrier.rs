@@ -1,12 +1,14 @@
 use std::{sync::Arc, time::Duration};
 
-use anyhow::anyhow;
 use log::warn;
 use tokio::time::sleep;
 
-use crate::{CrawlerState, RepositoryFetcher, Request, Response, StdResult};
+use crate::{CrawlerState, RepositoryFetcher, Request, Response, RetryPolicy, StdResult};
 
 /// A struct that retries a RepositoryFetcher a specified number of times in case of failure with exponential backoff strategy.
+///
+/// The retry policy of each error class is honored: permanent errors fail fast, and rate limited requests are
+/// retried after the delay requested by the API.
 pub struct FetcherRetrier {
     /// The fetcher to be retried.
     fetcher: Arc<dyn RepositoryFetcher>,
@@ -54,10 +56,18 @@ impl RepositoryFetcher for FetcherRetrier {
                 Err(e) => {
                     warn!("Fetch attempt #{} failed: {}", attempts + 1, e);
                     attempts += 1;
+                    let delay = match RetryPolicy::of(&e) {
+                        RetryPolicy::FailFast => {
+                            return Err(e.context("Fetch failed with a permanent error"));
+                        }
+                        RetryPolicy::Backoff => self.calculate_exponential_backoff_delay(attempts),
+                        RetryPolicy::RetryAfter(delay) => delay,
+                    };
                     if attempts >= self.max_retries {
-                        return Err(anyhow!("Failed after {} attempts: {}", attempts, e));
+                        return Err(e.context(format!("Failed after {attempts} attempts")));
                     }
-                    sleep(self.calculate_exponential_backoff_delay(attempts)).await;
+                    self.state.increment_total_fetcher_retries(1).await;
+                    sleep(delay).await;
                 }
             }
         }
@@ -70,7 +80,11 @@ impl RepositoryFetcher for FetcherRetrier {
 mod tests {
     use std::vec;
 
-    use crate::{FetcherRateLimit, MockRepositoryFetcher, Repository};
+    use anyhow::anyhow;
+
+    use crate::{
+        FetcherError, FetcherRateLimit, HttpRateLimitHeaders, MockRepositoryFetcher, Repository,
+    };
 
     use super::*;
 
@@ -160,6 +174,92 @@ mod tests {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn fetch_retries_after_retry_after_delay() {
+        let state = {
+            let state = CrawlerState::default();
+            state.set_total_repositories_target(10).await;
+            state
+                .push_request(Request::dummy_search_organization())
+                .await;
+
+            state
+        };
+        let fetcher = {
+            let mut fetcher = MockRepositoryFetcher::new();
+            fetcher
+                .expect_fetch()
+                .returning(|_| {
+                    Err(anyhow!(FetcherError::RateLimited {
+                        status: 403,
+                        headers: HttpRateLimitHeaders {
+                            retry_after: Some(Duration::from_millis(10)),
+                            ..Default::default()
+                        },
+                        message: "You have exceeded a secondary rate limit".to_string(),
+                    }))
+                })
+                .times(1);
+            fetcher.expect_fetch().returning(|_| Ok(None)).times(1);
+
+            fetcher
+        };
+        let retrier = FetcherRetrier::new(
+            Arc::new(fetcher),
+            3,
+            Duration::from_secs(3600),
+            Arc::new(state),
+        );
+
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            retrier.fetch(&Request::dummy_search_organization()),
+        )
+        .await
+        .expect("Retry should honor the Retry-After delay")
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn fetch_fails_fast_on_permanent_error() {
+        let state = {
+            let state = CrawlerState::default();
+            state.set_total_repositories_target(10).await;
+            state
+                .push_request(Request::dummy_search_organization())
+                .await;
+
+            state
+        };
+        let fetcher = {
+            let mut fetcher = MockRepositoryFetcher::new();
+            fetcher
+                .expect_fetch()
+                .returning(|_| Err(anyhow!(FetcherError::Auth("Bad credentials".to_string()))))
+                .times(1);
+
+            fetcher
+        };
+        let state = Arc::new(state);
+        let retrier = FetcherRetrier::new(
+            Arc::new(fetcher),
+            3,
+            Duration::from_secs(3600),
+            state.clone(),
+        );
+
+        let error = retrier
+            .fetch(&Request::dummy_search_organization())
+            .await
+            .expect_err("Fetch should fail fast on a permanent error");
+
+        assert!(matches!(
+            error.downcast_ref::<FetcherError>(),
+            Some(FetcherError::Auth(_))
+        ));
+        assert_eq!(0, state.get_total_fetcher_retries().await);
+    }
+
     #[tokio::test]
     async fn fetch_failure_after_max_retries() {
         let state = {
@@ -175,7 +275,7 @@ mod tests {
             let mut fetcher = MockRepositoryFetcher::new();
             fetcher
                 .expect_fetch()
-                .returning(|_| Err(anyhow!("Error fetching data")))
+                .returning(|_| Err(anyhow!(FetcherError::Network("timeout".to_string()))))
                 .times(3);
 
             fetcher
@@ -187,9 +287,13 @@ mod tests {
             Arc::new(state),
         );
 
-        retrier
+        let error = retrier
             .fetch(&Request::dummy_search_organization())
             .await
             .expect_err("Expected failure after max retries");
+        assert!(matches!(
+            error.downcast_ref::<FetcherError>(),
+            Some(FetcherError::Network(_))
+        ));
     }
 }
@@ -0,0 +1,428 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use anyhow::{Context, anyhow};
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use tokio::{sync::Mutex, time::sleep};
+
+use crate::{
+    CredentialProvider, FETCHER_RATE_LIMIT_MIN_REMAINING_ALLOWED, FetcherRateLimit,
+    StaticTokenProvider, StdResult,
+};
+
+/// An API token of the pool.
+struct ApiToken {
+    /// The label of the token, used in logs instead of the secret.
+    label: String,
+
+    /// The provider of the secret value of the token.
+    credential_provider: Arc<dyn CredentialProvider>,
+}
+
+/// The rate limit budget of an API token, shared by all the workers.
+#[derive(Debug, Clone, Default)]
+struct TokenBudget {
+    /// The latest known rate limit of the token.
+    rate_limit: Option<FetcherRateLimit>,
+
+    /// The number of requests in flight with the token, whose cost is reserved on the budget.
+    total_reserved_requests: i32,
+
+    /// The time before which no other request is sent with the token, to spread its budget over the reset window.
+    next_request_at: Option<DateTime<Utc>>,
+}
+
+impl TokenBudget {
+    /// Estimates the cost of a request from the cost of the latest request.
+    fn estimated_cost(&self) -> i32 {
+        self.rate_limit
+            .as_ref()
+            .map_or(1, |rate_limit| rate_limit.cost.max(1))
+    }
+
+    /// Computes the points available for new requests and the duration until the reset of the rate limit.
+    fn available_points(&self, now: DateTime<Utc>) -> (i32, Duration) {
+        let reserved_points = self.total_reserved_requests * self.estimated_cost();
+        match &self.rate_limit {
+            None => (i32::MAX - reserved_points, Duration::ZERO),
+            Some(rate_limit) => match rate_limit.duration_until_reset(now) {
+                Ok(duration_until_reset) if !duration_until_reset.is_zero() => {
+                    (rate_limit.remaining - reserved_points, duration_until_reset)
+                }
+                _ => (rate_limit.limit - reserved_points, Duration::ZERO),
+            },
+        }
+    }
+
+    /// Reserves the cost of a request and returns the delay before sending it.
+    ///
+    /// The requests are spaced so that the available points are spent evenly until the reset of the rate limit.
+    fn reserve(&mut self, now: DateTime<Utc>) -> Duration {
+        let (available_points, duration_until_reset) = self.available_points(now);
+        let total_available_requests = (available_points / self.estimated_cost()).max(1) as u32;
+        let interval = chrono::Duration::from_std(duration_until_reset / total_available_requests)
+            .unwrap_or_default();
+        let request_at = self.next_request_at.map_or(now, |next| next.max(now));
+        self.next_request_at = Some(request_at + interval);
+        self.total_reserved_requests += 1;
+
+        (request_at - now).to_std().unwrap_or_default()
+    }
+
+    /// Releases the reservation of a request, and records the rate limit of its response if any.
+    fn release(&mut self, rate_limit: Option<FetcherRateLimit>) {
+        self.total_reserved_requests = (self.total_reserved_requests - 1).max(0);
+        if let Some(mut rate_limit) = rate_limit {
+            // The responses may arrive out of order within the same rate limit window
+            if let Some(previous) = &self.rate_limit
+                && previous.reset_at == rate_limit.reset_at
+            {
+                rate_limit.remaining = rate_limit.remaining.min(previous.remaining);
+            }
+            self.rate_limit = Some(rate_limit);
+        }
+    }
+}
+
+/// A pool of API tokens, each with its own rate limit budget.
+///
+/// Requests are dispatched to the token with the most remaining budget, and exhausted tokens are parked until
+/// their rate limit resets. The cost of each request is reserved on the budget before it is sent, and the
+/// requests of each token are spread evenly over its reset window.
+pub struct TokenPool {
+    /// The API tokens.
+    tokens: Vec<ApiToken>,
+
+    /// The rate limit budget of each token (by index).
+    budgets: Mutex<Vec<TokenBudget>>,
+
+    /// The time until which no request is sent with any token, after a secondary rate limit.
+    throttled_until: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl TokenPool {
+    /// Creates a new `TokenPool` instance with the given credential providers.
+    pub fn try_new(credential_providers: Vec<Arc<dyn CredentialProvider>>) -> StdResult<Self> {
+        let tokens = credential_providers
+            .into_iter()
+            .enumerate()
+            .map(|(index, credential_provider)| ApiToken {
+                label: format!("token-{}", index + 1),
+                credential_provider,
+            })
+            .collect::<Vec<_>>();
+        if tokens.is_empty() {
+            return Err(anyhow!("At least one API token is required"));
+        }
+        let budgets = Mutex::new(vec![TokenBudget::default(); tokens.len()]);
+
+        Ok(Self {
+            tokens,
+            budgets,
+            throttled_until: Mutex::new(None),
+        })
+    }
+
+    /// Creates a new `TokenPool` instance with the given static token secrets.
+    pub fn try_from_secrets(secrets: Vec<String>) -> StdResult<Self> {
+        Self::try_new(
+            secrets
+                .iter()
+                .map(|secret| secret.trim())
+                .filter(|secret| !secret.is_empty())
+                .map(|secret| {
+                    Arc::new(StaticTokenProvider::new(secret)) as Arc<dyn CredentialProvider>
+                })
+                .collect(),
+        )
+    }
+
+    /// Creates a new `TokenPool` instance from the comma-separated tokens of the `GITHUB_API_TOKEN` environment variable.
+    pub fn try_from_env() -> StdResult<Self> {
+        let github_api_token = std::env::var("GITHUB_API_TOKEN")
+            .with_context(|| "Missing GITHUB_API_TOKEN environment variable")?;
+
+        Self::try_from_secrets(github_api_token.split(',').map(String::from).collect())
+    }
+
+    /// Creates a new `TokenPool` instance from a file containing one token per line.
+    pub fn try_from_file(path: &Path) -> StdResult<Self> {
+        let tokens = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read API tokens file {path:?}"))?;
+
+        Self::try_from_secrets(tokens.lines().map(String::from).collect())
+    }
+
+    /// Retrieves the number of tokens in the pool.
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Returns `true` if the pool has no token.
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Retrieves the label of the token at the given index.
+    pub fn label(&self, index: usize) -> &str {
+        &self.tokens[index].label
+    }
+
+    /// Retrieves the secret of the token at the given index from its credential provider.
+    pub async fn secret(&self, index: usize) -> StdResult<String> {
+        self.tokens[index].credential_provider.token().await
+    }
+
+    /// Acquires the index of the token with the most remaining budget, and reserves the cost of a request on it.
+    ///
+    /// Waits until the request can be sent without bursting the budget of the token, or until the earliest rate
+    /// limit reset if all the tokens are exhausted. The reservation is released by `update` or `release`.
+    pub async fn acquire(&self) -> usize {
+        loop {
+            if let Some(throttle_delay) = self.throttle_delay(Utc::now()).await {
+                warn!("API requests are throttled, waiting for {throttle_delay:?}");
+                sleep(throttle_delay).await;
+                continue;
+            }
+            match self.select_token(Utc::now()).await {
+                Ok((index, delay)) => {
+                    if !delay.is_zero() {
+                        debug!("Delaying request with {} by {delay:?}", self.label(index));
+                        sleep(delay).await;
+                    }
+                    return index;
+                }
+                Err(duration_until_reset) => {
+                    warn!(
+                        "All API tokens are exhausted, waiting for {duration_until_reset:?} until the earliest reset"
+                    );
+                    sleep(duration_until_reset).await;
+                }
+            }
+        }
+    }
+
+    /// Updates the rate limit of the token at the given index from a response, releasing its reservation.
+    pub async fn update(&self, index: usize, rate_limit: FetcherRateLimit) {
+        let mut budgets = self.budgets.lock().await;
+        budgets[index].release(Some(rate_limit));
+    }
+
+    /// Releases the reservation of a request that failed without a rate limit.
+    pub async fn release(&self, index: usize) {
+        let mut budgets = self.budgets.lock().await;
+        budgets[index].release(None);
+    }
+
+    /// Pauses the requests of all the tokens for the given delay, after a secondary rate limit.
+    pub async fn throttle(&self, delay: Duration) {
+        let throttled_until = Utc::now() + chrono::Duration::from_std(delay).unwrap_or_default();
+        let mut current_throttled_until = self.throttled_until.lock().await;
+        if current_throttled_until.is_none_or(|current| current < throttled_until) {
+            *current_throttled_until = Some(throttled_until);
+        }
+    }
+
+    /// Retrieves the remaining delay of the throttling of the requests, if any.
+    pub async fn throttle_delay(&self, now: DateTime<Utc>) -> Option<Duration> {
+        let throttled_until = self.throttled_until.lock().await;
+        throttled_until
+            .and_then(|throttled_until| (throttled_until - now).to_std().ok())
+            .filter(|delay| !delay.is_zero())
+    }
+
+    async fn select_token(&self, now: DateTime<Utc>) -> Result<(usize, Duration), Duration> {
+        let mut budgets = self.budgets.lock().await;
+        let mut selected_token: Option<(usize, DateTime<Utc>, i32)> = None;
+        let mut duration_until_reset: Option<Duration> = None;
+        for (index, budget) in budgets.iter().enumerate() {
+            let (available_points, duration) = budget.available_points(now);
+            let is_exhausted = available_points - budget.estimated_cost()
+                < FETCHER_RATE_LIMIT_MIN_REMAINING_ALLOWED;
+            if is_exhausted && !duration.is_zero() {
+                duration_until_reset =
+                    Some(duration_until_reset.map_or(duration, |other| other.min(duration)));
+                continue;
+            }
+            // The token that can send a request the earliest is selected, then the one with the most points
+            let request_at = budget.next_request_at.map_or(now, |next| next.max(now));
+            if selected_token.is_none_or(|(_, selected_request_at, selected_points)| {
+                (request_at, -available_points) < (selected_request_at, -selected_points)
+            }) {
+                selected_token = Some((index, request_at, available_points));
+            }
+        }
+
+        match (selected_token, duration_until_reset) {
+            (Some((index, _, _)), _) => Ok((index, budgets[index].reserve(now))),
+            (None, duration_until_reset) => Err(duration_until_reset.unwrap_or_default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate_limit(remaining: i32, reset_at: DateTime<Utc>) -> FetcherRateLimit {
+        FetcherRateLimit {
+            limit: 5000,
+            cost: 1,
+            remaining,
+            reset_at: reset_at.to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn try_from_secrets_fails_without_tokens() {
+        let result = TokenPool::try_from_secrets(vec![" ".to_string()]);
+
+        assert!(result.is_err(), "Should fail without tokens");
+    }
+
+    #[tokio::test]
+    async fn try_from_secrets_labels_tokens() {
+        let pool =
+            TokenPool::try_from_secrets(vec!["secret-1".to_string(), " secret-2 ".to_string()])
+                .unwrap();
+
+        assert_eq!(2, pool.len());
+        assert_eq!("token-2", pool.label(1));
+        assert_eq!("secret-2", pool.secret(1).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn acquire_token_with_most_remaining_budget() {
+        let pool =
+            TokenPool::try_from_secrets(vec!["secret-1".to_string(), "secret-2".to_string()])
+                .unwrap();
+        let reset_at = Utc::now() + chrono::Duration::seconds(60);
+        pool.update(0, rate_limit(100, reset_at)).await;
+        pool.update(1, rate_limit(200, reset_at)).await;
+
+        assert_eq!(1, pool.acquire().await);
+    }
+
+    #[tokio::test]
+    async fn acquire_token_never_used_first() {
+        let pool =
+            TokenPool::try_from_secrets(vec!["secret-1".to_string(), "secret-2".to_string()])
+                .unwrap();
+        pool.update(0, rate_limit(4999, Utc::now())).await;
+
+        assert_eq!(1, pool.acquire().await);
+    }
+
+    #[tokio::test]
+    async fn acquire_skips_exhausted_tokens() {
+        let pool =
+            TokenPool::try_from_secrets(vec!["secret-1".to_string(), "secret-2".to_string()])
+                .unwrap();
+        let reset_at = Utc::now() + chrono::Duration::seconds(60);
+        pool.update(0, rate_limit(0, reset_at)).await;
+        pool.update(1, rate_limit(20, reset_at)).await;
+
+        assert_eq!(1, pool.acquire().await);
+    }
+
+    #[tokio::test]
+    async fn acquire_reuses_exhausted_token_after_reset() {
+        let pool = TokenPool::try_from_secrets(vec!["secret-1".to_string()]).unwrap();
+        let reset_at = Utc::now() - chrono::Duration::seconds(60);
+        pool.update(0, rate_limit(0, reset_at)).await;
+
+        assert_eq!(0, pool.acquire().await);
+    }
+
+    #[tokio::test]
+    async fn select_token_reserves_points_until_exhaustion() {
+        let pool = TokenPool::try_from_secrets(vec!["secret-1".to_string()]).unwrap();
+        let now = Utc::now();
+        pool.update(0, rate_limit(12, now + chrono::Duration::seconds(60)))
+            .await;
+
+        assert!(pool.select_token(now).await.is_ok());
+        assert!(pool.select_token(now).await.is_ok());
+        assert!(pool.select_token(now).await.is_err());
+        pool.release(0).await;
+        assert!(pool.select_token(now).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn select_token_spreads_requests_over_reset_window() {
+        let pool = TokenPool::try_from_secrets(vec!["secret-1".to_string()]).unwrap();
+        let now = Utc::now();
+        pool.update(0, rate_limit(60, now + chrono::Duration::seconds(60)))
+            .await;
+
+        let (_, first_delay) = pool.select_token(now).await.unwrap();
+        let (_, second_delay) = pool.select_token(now).await.unwrap();
+
+        assert_eq!(Duration::ZERO, first_delay);
+        assert!(second_delay >= Duration::from_millis(900));
+        assert!(second_delay <= Duration::from_millis(1100));
+    }
+
+    #[tokio::test]
+    async fn select_token_dispatches_to_token_available_earliest() {
+        let pool =
+            TokenPool::try_from_secrets(vec!["secret-1".to_string(), "secret-2".to_string()])
+                .unwrap();
+        let now = Utc::now();
+        let reset_at = now + chrono::Duration::seconds(60);
+        pool.update(0, rate_limit(200, reset_at)).await;
+        pool.update(1, rate_limit(100, reset_at)).await;
+
+        let (first_index, _) = pool.select_token(now).await.unwrap();
+        let (second_index, second_delay) = pool.select_token(now).await.unwrap();
+
+        assert_eq!(0, first_index);
+        assert_eq!(1, second_index);
+        assert_eq!(Duration::ZERO, second_delay);
+    }
+
+    #[tokio::test]
+    async fn throttle_pauses_all_tokens() {
+        let pool =
+            TokenPool::try_from_secrets(vec!["secret-1".to_string(), "secret-2".to_string()])
+                .unwrap();
+
+        pool.throttle(Duration::from_secs(30)).await;
+        pool.throttle(Duration::from_secs(10)).await;
+        let throttle_delay = pool.throttle_delay(Utc::now()).await.unwrap();
+
+        assert!(throttle_delay > Duration::from_secs(29));
+        assert!(throttle_delay <= Duration::from_secs(30));
+        assert_eq!(
+            None,
+            pool.throttle_delay(Utc::now() + chrono::Duration::seconds(31))
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn update_keeps_lowest_remaining_of_rate_limit_window() {
+        let pool = TokenPool::try_from_secrets(vec!["secret-1".to_string()]).unwrap();
+        let now = Utc::now();
+        let reset_at = now + chrono::Duration::seconds(60);
+        pool.update(0, rate_limit(100, reset_at)).await;
+        pool.update(0, rate_limit(120, reset_at)).await;
+
+        let budgets = pool.budgets.lock().await;
+
+        assert_eq!(100, budgets[0].rate_limit.as_ref().unwrap().remaining);
+    }
+
+    #[tokio::test]
+    async fn acquire_waits_until_earliest_reset_when_all_tokens_are_exhausted() {
+        let pool = TokenPool::try_from_secrets(vec!["secret-1".to_string()]).unwrap();
+        let now = Utc::now();
+        pool.update(0, rate_limit(0, now + chrono::Duration::seconds(1)))
+            .await;
+
+        let duration_until_reset = pool.select_token(now).await.unwrap_err();
+
+        assert!(duration_until_reset >= Duration::from_secs(1));
+        assert!(duration_until_reset <= Duration::from_secs(2));
+    }
+}
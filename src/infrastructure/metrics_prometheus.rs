@@ -0,0 +1,457 @@
+use std::{fmt::Write as _, net::SocketAddr, sync::Arc, time::Duration};
+
+use anyhow::anyhow;
+use chrono::DateTime;
+use log::{info, warn};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+use crate::{CrawlerState, StdResult};
+
+/// The prefix of the names of the exposed metrics.
+const METRICS_PREFIX: &str = "github_crawler";
+
+/// The maximum size of the HTTP request read from a scraper.
+const MAX_REQUEST_SIZE: usize = 8192;
+
+/// The time given to a scraper to send its HTTP request before its connection is closed.
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An HTTP server exposing the metrics of the crawler state in the Prometheus text format on `/metrics`.
+pub struct PrometheusMetricsServer {
+    /// The listener accepting the scrapers connections
+    listener: TcpListener,
+
+    /// The state of the crawler
+    state: Arc<CrawlerState>,
+}
+
+impl PrometheusMetricsServer {
+    /// Creates a new `PrometheusMetricsServer` instance listening on the given address.
+    pub async fn try_bind(address: SocketAddr, state: Arc<CrawlerState>) -> StdResult<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address).await?,
+            state,
+        })
+    }
+
+    /// Retrieves the address the server is listening on.
+    pub fn local_addr(&self) -> StdResult<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serves the metrics until the task is dropped.
+    pub async fn serve(self) {
+        let server = Arc::new(self);
+        loop {
+            match server.listener.accept().await {
+                Ok((stream, _)) => {
+                    let server = server.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = server.handle_connection(stream).await {
+                            info!("Failed to serve metrics: {e}");
+                        }
+                    });
+                }
+                Err(e) => warn!("Failed to accept metrics connection: {e}"),
+            }
+        }
+    }
+
+    /// Reads the head of the HTTP request of a scraper, failing if it exceeds `MAX_REQUEST_SIZE` bytes.
+    async fn read_request(stream: &mut TcpStream) -> StdResult<Vec<u8>> {
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+            let max_read = buffer.len().min(MAX_REQUEST_SIZE - request.len());
+            if max_read == 0 {
+                return Err(anyhow!(
+                    "Metrics request larger than {MAX_REQUEST_SIZE} bytes"
+                ));
+            }
+            let total_read = stream.read(&mut buffer[..max_read]).await?;
+            if total_read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..total_read]);
+        }
+
+        Ok(request)
+    }
+
+    async fn handle_connection(&self, mut stream: TcpStream) -> StdResult<()> {
+        let request = timeout(REQUEST_READ_TIMEOUT, Self::read_request(&mut stream))
+            .await
+            .map_err(|_| anyhow!("Timed out reading metrics request"))??;
+        let request_line = String::from_utf8_lossy(&request)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let response = match request_line.split_whitespace().collect::<Vec<_>>()[..] {
+            ["GET", "/metrics", ..] => {
+                let body = self.render().await;
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+            }
+            _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                .to_string(),
+        };
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await?;
+
+        Ok(())
+    }
+
+    /// Renders the metrics of the crawler state in the Prometheus text format.
+    pub async fn render(&self) -> String {
+        let mut metrics = MetricsWriter::default();
+        let state = &self.state;
+        metrics.write_metric(
+            "persisted_repositories_total",
+            "counter",
+            "The total number of repositories persisted",
+            &[(
+                vec![],
+                state.get_total_persisted_repositories().await as f64,
+            )],
+        );
+        metrics.write_metric(
+            "collisions_repositories_total",
+            "counter",
+            "The total number of repositories that already existed",
+            &[(
+                vec![],
+                state.get_total_collisions_repositories().await as f64,
+            )],
+        );
+        metrics.write_metric(
+            "refreshed_repositories_total",
+            "counter",
+            "The total number of known repositories refreshed",
+            &[(
+                vec![],
+                state.get_total_refreshed_repositories().await as f64,
+            )],
+        );
+        metrics.write_metric(
+            "unreachable_repositories_total",
+            "counter",
+            "The total number of known repositories marked as deleted or inaccessible",
+            &[(
+                vec![],
+                state.get_total_unreachable_repositories().await as f64,
+            )],
+        );
+        metrics.write_metric(
+            "pending_repositories",
+            "gauge",
+            "The number of repositories buffered but not yet persisted",
+            &[(vec![], state.get_total_pending_repositories().await as f64)],
+        );
+        metrics.write_metric(
+            "target_repositories",
+            "gauge",
+            "The total number of repositories to be fetched",
+            &[(vec![], state.get_total_repositories_target().await as f64)],
+        );
+        metrics.write_metric(
+            "fetcher_calls_total",
+            "counter",
+            "The total number of fetcher calls made",
+            &[(vec![], state.get_total_fetcher_calls().await as f64)],
+        );
+        metrics.write_metric(
+            "queued_requests",
+            "gauge",
+            "The number of requests waiting in the queue",
+            &[(vec![], state.get_total_queued_requests().await as f64)],
+        );
+        metrics.write_metric(
+            "in_progress_requests",
+            "gauge",
+            "The number of requests in progress",
+            &[(vec![], state.get_total_in_progress_requests().await as f64)],
+        );
+        metrics.write_metric(
+            "fetcher_retries_total",
+            "counter",
+            "The total number of fetch attempts that were retried",
+            &[(vec![], state.get_total_fetcher_retries().await as f64)],
+        );
+        metrics.write_metric(
+            "persister_retries_total",
+            "counter",
+            "The total number of persist attempts that were retried",
+            &[(vec![], state.get_total_persister_retries().await as f64)],
+        );
+        metrics.write_metric(
+            "parse_errors_total",
+            "counter",
+            "The total number of responses and search results skipped because they could not be parsed",
+            &[(vec![], state.get_total_parse_errors().await as f64)],
+        );
+        metrics.write_metric(
+            "node_errors_total",
+            "counter",
+            "The total number of node-level errors of partial GraphQL responses",
+            &[(vec![], state.get_total_node_errors().await as f64)],
+        );
+        metrics.write_metric(
+            "failed_requests_total",
+            "counter",
+            "The total number of requests skipped after a permanent error",
+            &[(vec![], state.get_total_failed_requests().await as f64)],
+        );
+        metrics.write_metric(
+            "page_size",
+            "gauge",
+            "The page size adapted for the requests, by request kind",
+            &state
+                .get_page_sizes()
+                .await
+                .iter()
+                .map(|(request_kind, first)| {
+                    (vec![("request_kind", request_kind.as_str())], *first as f64)
+                })
+                .collect::<Vec<_>>(),
+        );
+        metrics.write_metric(
+            "page_size_adjustments_total",
+            "counter",
+            "The total number of adjustments of the page size of the requests",
+            &[(vec![], state.get_total_page_size_adjustments().await as f64)],
+        );
+
+        let api_rate_limits = state.get_api_rate_limits().await;
+        metrics.write_metric(
+            "rate_limit_remaining",
+            "gauge",
+            "The remaining API rate limit points, by API token",
+            &api_rate_limits
+                .iter()
+                .map(|(api_token, rate_limit)| {
+                    (
+                        vec![("api_token", api_token.as_str())],
+                        rate_limit.remaining as f64,
+                    )
+                })
+                .collect::<Vec<_>>(),
+        );
+        metrics.write_metric(
+            "rate_limit_reset_timestamp_seconds",
+            "gauge",
+            "The time at which the API rate limit resets, by API token",
+            &api_rate_limits
+                .iter()
+                .filter_map(|(api_token, rate_limit)| {
+                    DateTime::parse_from_rfc3339(&rate_limit.reset_at)
+                        .ok()
+                        .map(|reset_at| {
+                            (
+                                vec![("api_token", api_token.as_str())],
+                                reset_at.timestamp() as f64,
+                            )
+                        })
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        let request_latencies = state.get_request_latencies().await;
+        metrics.write_header(
+            "request_duration_seconds",
+            "histogram",
+            "The latency of the requests, by request kind",
+        );
+        for (request_kind, histogram) in &request_latencies {
+            for (upper_bound, cumulative_count) in histogram.cumulative_buckets() {
+                metrics.write_sample(
+                    "request_duration_seconds_bucket",
+                    &[
+                        ("request_kind", request_kind),
+                        ("le", &upper_bound.to_string()),
+                    ],
+                    cumulative_count as f64,
+                );
+            }
+            metrics.write_sample(
+                "request_duration_seconds_bucket",
+                &[("request_kind", request_kind), ("le", "+Inf")],
+                histogram.count() as f64,
+            );
+            metrics.write_sample(
+                "request_duration_seconds_sum",
+                &[("request_kind", request_kind)],
+                histogram.sum_seconds(),
+            );
+            metrics.write_sample(
+                "request_duration_seconds_count",
+                &[("request_kind", request_kind)],
+                histogram.count() as f64,
+            );
+        }
+
+        metrics.0
+    }
+}
+
+/// A writer of metrics in the Prometheus text format.
+#[derive(Default)]
+struct MetricsWriter(String);
+
+impl MetricsWriter {
+    fn write_header(&mut self, name: &str, metric_type: &str, help: &str) {
+        let _ = writeln!(self.0, "# HELP {METRICS_PREFIX}_{name} {help}");
+        let _ = writeln!(self.0, "# TYPE {METRICS_PREFIX}_{name} {metric_type}");
+    }
+
+    fn write_sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let labels = labels
+            .iter()
+            .map(|(label, label_value)| {
+                format!(
+                    "{label}=\"{}\"",
+                    label_value
+                        .replace('\\', "\\\\")
+                        .replace('"', "\\\"")
+                        .replace('\n', "\\n")
+                )
+            })
+            .collect::<Vec<_>>();
+        let labels = match labels.is_empty() {
+            true => String::new(),
+            false => format!("{{{}}}", labels.join(",")),
+        };
+        let _ = writeln!(self.0, "{METRICS_PREFIX}_{name}{labels} {value}");
+    }
+
+    fn write_metric(
+        &mut self,
+        name: &str,
+        metric_type: &str,
+        help: &str,
+        samples: &[(Vec<(&str, &str)>, f64)],
+    ) {
+        self.write_header(name, metric_type, help);
+        for (labels, value) in samples {
+            self.write_sample(name, labels, *value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::FetcherRateLimit;
+
+    use super::*;
+
+    async fn setup_server() -> PrometheusMetricsServer {
+        let state = Arc::new(CrawlerState::default());
+        state.increment_total_persisted_repositories(12).await;
+        state.increment_total_fetcher_retries(3).await;
+        state
+            .update_api_rate_limit(
+                "token-\"1\"",
+                FetcherRateLimit {
+                    limit: 5000,
+                    cost: 1,
+                    remaining: 4321,
+                    reset_at: "2025-01-01T00:00:00Z".to_string(),
+                },
+            )
+            .await;
+        state
+            .observe_request_latency("search_organization", Duration::from_millis(300))
+            .await;
+
+        PrometheusMetricsServer::try_bind("127.0.0.1:0".parse().unwrap(), state)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn render_metrics() {
+        let server = setup_server().await;
+
+        let metrics = server.render().await;
+
+        for expected_line in [
+            "# TYPE github_crawler_persisted_repositories_total counter",
+            "github_crawler_persisted_repositories_total 12",
+            "github_crawler_fetcher_retries_total 3",
+            "github_crawler_rate_limit_remaining{api_token=\"token-\\\"1\\\"\"} 4321",
+            "github_crawler_rate_limit_reset_timestamp_seconds{api_token=\"token-\\\"1\\\"\"} 1735689600",
+            "github_crawler_request_duration_seconds_bucket{request_kind=\"search_organization\",le=\"0.25\"} 0",
+            "github_crawler_request_duration_seconds_bucket{request_kind=\"search_organization\",le=\"0.5\"} 1",
+            "github_crawler_request_duration_seconds_bucket{request_kind=\"search_organization\",le=\"+Inf\"} 1",
+            "github_crawler_request_duration_seconds_count{request_kind=\"search_organization\"} 1",
+        ] {
+            assert!(
+                metrics.lines().any(|line| line == expected_line),
+                "Missing line {expected_line} in:\n{metrics}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn serve_metrics_over_http() {
+        let server = setup_server().await;
+        let address = server.local_addr().unwrap();
+        tokio::spawn(server.serve());
+
+        let response = reqwest::get(format!("http://{address}/metrics"))
+            .await
+            .unwrap();
+        let not_found_response = reqwest::get(format!("http://{address}/unknown"))
+            .await
+            .unwrap();
+
+        assert_eq!(200, response.status().as_u16());
+        assert!(
+            response
+                .text()
+                .await
+                .unwrap()
+                .contains("github_crawler_persisted_repositories_total 12")
+        );
+        assert_eq!(404, not_found_response.status().as_u16());
+    }
+
+    #[tokio::test]
+    async fn serve_closes_idle_connections() {
+        let server = setup_server().await;
+        let address = server.local_addr().unwrap();
+        tokio::spawn(server.serve());
+        let mut stream = TcpStream::connect(address).await.unwrap();
+
+        let mut response = Vec::new();
+        let result = timeout(REQUEST_READ_TIMEOUT * 2, stream.read_to_end(&mut response))
+            .await
+            .expect("Idle connection should be closed");
+
+        assert!(result.is_err() || response.is_empty());
+    }
+
+    #[tokio::test]
+    async fn serve_closes_oversized_requests() {
+        let server = setup_server().await;
+        let address = server.local_addr().unwrap();
+        tokio::spawn(server.serve());
+        let mut stream = TcpStream::connect(address).await.unwrap();
+
+        let _ = stream
+            .write_all(format!("GET /metrics?{} HTTP/1.1", "a".repeat(MAX_REQUEST_SIZE)).as_bytes())
+            .await;
+        let mut response = Vec::new();
+        let result = stream.read_to_end(&mut response).await;
+
+        assert!(result.is_err() || response.is_empty());
+    }
+}
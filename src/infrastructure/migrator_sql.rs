@@ -0,0 +1,117 @@
+use std::str::FromStr;
+
+use log::info;
+use sqlx::{
+    PgPool, SqlitePool,
+    migrate::Migrator,
+    postgres::PgPoolOptions,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+
+use crate::StdResult;
+
+/// The versioned migrations of the PostgreSQL database schema, embedded in the binary.
+pub static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migration/postgres");
+
+/// The versioned migrations of the SQLite database schema, embedded in the binary.
+pub static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migration/sqlite");
+
+/// Counts the successful migrations recorded in the migrations table, which is missing before the first migration.
+const COUNT_APPLIED_MIGRATIONS_QUERY: &str = "SELECT COUNT(*) FROM _sqlx_migrations WHERE success";
+
+/// The scheme of the SQLite connection strings.
+pub const SQLITE_SCHEME: &str = "sqlite:";
+
+/// A migrator that applies the pending migrations to a database selected by the scheme of its connection string.
+///
+/// The applied migrations are recorded in the `_sqlx_migrations` table of the database.
+pub enum DatabaseMigrator {
+    /// A PostgreSQL database.
+    Postgres(PgPool),
+
+    /// An SQLite database, created if missing.
+    Sqlite(SqlitePool),
+}
+
+impl DatabaseMigrator {
+    /// Creates a new `DatabaseMigrator` instance.
+    pub async fn try_new(connection_string: &str) -> StdResult<Self> {
+        Ok(if connection_string.starts_with(SQLITE_SCHEME) {
+            Self::Sqlite(
+                SqlitePoolOptions::new()
+                    .max_connections(1)
+                    .connect_with(
+                        SqliteConnectOptions::from_str(connection_string)?.create_if_missing(true),
+                    )
+                    .await?,
+            )
+        } else {
+            Self::Postgres(
+                PgPoolOptions::new()
+                    .max_connections(1)
+                    .connect(connection_string)
+                    .await?,
+            )
+        })
+    }
+
+    /// Applies the pending migrations and returns the number of migrations applied.
+    pub async fn migrate(&self) -> StdResult<usize> {
+        let (total_applied, migrator) = match self {
+            Self::Postgres(pool) => {
+                let count_applied_migrations = || async {
+                    sqlx::query_scalar::<_, i64>(COUNT_APPLIED_MIGRATIONS_QUERY)
+                        .fetch_one(pool)
+                        .await
+                        .unwrap_or_default()
+                };
+                let total_applied_before = count_applied_migrations().await;
+                POSTGRES_MIGRATOR.run(pool).await?;
+                (
+                    count_applied_migrations().await - total_applied_before,
+                    &POSTGRES_MIGRATOR,
+                )
+            }
+            Self::Sqlite(pool) => {
+                let count_applied_migrations = || async {
+                    sqlx::query_scalar::<_, i64>(COUNT_APPLIED_MIGRATIONS_QUERY)
+                        .fetch_one(pool)
+                        .await
+                        .unwrap_or_default()
+                };
+                let total_applied_before = count_applied_migrations().await;
+                SQLITE_MIGRATOR.run(pool).await?;
+                (
+                    count_applied_migrations().await - total_applied_before,
+                    &SQLITE_MIGRATOR,
+                )
+            }
+        };
+        info!(
+            "Applied {total_applied} migration(s), database schema at version {}",
+            migrator
+                .iter()
+                .map(|migration| migration.version)
+                .max()
+                .unwrap_or_default()
+        );
+
+        Ok(total_applied as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn migrate_sqlite_database_once() {
+        let migrator = DatabaseMigrator::try_new("sqlite::memory:").await.unwrap();
+
+        let total_applied = migrator.migrate().await.unwrap();
+        let total_applied_again = migrator.migrate().await.unwrap();
+
+        assert_eq!(SQLITE_MIGRATOR.iter().count(), total_applied);
+        assert_eq!(0, total_applied_again);
+    }
+}
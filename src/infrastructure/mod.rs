@@ -1,15 +1,49 @@
+mod checkpointer_file;
+mod checkpointer_postgresql;
+mod crawler_checkpoint;
 mod crawler_parallel;
 mod crawler_worker;
+mod credentials_github_app;
+mod credentials_static;
+mod exporter_csv;
+mod exporter_jsonl;
+mod exporter_parquet;
 mod fetcher_graphql;
+mod fetcher_page_size;
 mod fetcher_rate_limiter;
 mod fetcher_retrier;
+mod fetcher_token_pool;
+mod metrics_prometheus;
+mod migrator_sql;
+mod persister_buffered;
+mod persister_csv;
+mod persister_jsonl;
 mod persister_postgresql;
 mod persister_retrier;
+mod persister_sqlite;
+mod reader_postgresql;
 
+pub use checkpointer_file::*;
+pub use checkpointer_postgresql::*;
+pub use crawler_checkpoint::*;
 pub use crawler_parallel::*;
 pub use crawler_worker::*;
+pub use credentials_github_app::*;
+pub use credentials_static::*;
+pub use exporter_csv::*;
+pub use exporter_jsonl::*;
+pub use exporter_parquet::*;
 pub use fetcher_graphql::*;
+pub use fetcher_page_size::*;
 pub use fetcher_rate_limiter::*;
 pub use fetcher_retrier::*;
+pub use fetcher_token_pool::*;
+pub use metrics_prometheus::*;
+pub use migrator_sql::*;
+pub use persister_buffered::*;
+pub use persister_csv::*;
+pub use persister_jsonl::*;
 pub use persister_postgresql::*;
 pub use persister_retrier::*;
+pub use persister_sqlite::*;
+pub use reader_postgresql::*;
@@ -0,0 +1,405 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::anyhow;
+use log::{error, warn};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{MissedTickBehavior, interval},
+};
+
+use crate::{CrawlerState, PersistReport, Repository, RepositoryPersister, StdResult};
+
+/// The number of consecutive failed flushes after which the buffered repositories are abandoned.
+const MAX_CONSECUTIVE_FLUSH_FAILURES: u32 = 3;
+
+/// A message sent to the background task of a `BufferedPersister`.
+enum BufferMessage {
+    /// A repository to be persisted.
+    Repository(Box<Repository>),
+
+    /// A request to persist the buffered repositories, answered once they are committed.
+    Flush(oneshot::Sender<StdResult<()>>),
+}
+
+/// A persister that buffers repositories and persists them in batches from a background task.
+///
+/// The repositories are persisted once the buffer is full or the flush interval has elapsed, and the
+/// totals of the crawler state are only incremented once the repositories are committed. While a full
+/// buffer can not be persisted, no more repositories are buffered and the callers wait, until the
+/// buffer is abandoned after `MAX_CONSECUTIVE_FLUSH_FAILURES` consecutive failed flushes.
+pub struct BufferedPersister {
+    /// The sender of the messages to the background task.
+    sender: mpsc::Sender<BufferMessage>,
+
+    /// The persister to which the unreachable repositories are marked without buffering.
+    persister: Arc<dyn RepositoryPersister>,
+
+    /// The state of the crawler
+    state: Arc<CrawlerState>,
+
+    /// The error of the latest background flush, reported by the next call.
+    error: Arc<std::sync::Mutex<Option<anyhow::Error>>>,
+}
+
+impl BufferedPersister {
+    /// Creates a new `BufferedPersister` instance buffering up to `buffer_size` repositories.
+    pub fn new(
+        persister: Arc<dyn RepositoryPersister>,
+        state: Arc<CrawlerState>,
+        buffer_size: usize,
+        flush_interval: Duration,
+    ) -> Self {
+        let buffer_size = buffer_size.max(1);
+        let (sender, receiver) = mpsc::channel(buffer_size);
+        let error = Arc::new(std::sync::Mutex::new(None));
+        let buffer = Buffer {
+            persister: persister.clone(),
+            state: state.clone(),
+            repositories: Vec::with_capacity(buffer_size),
+            buffer_size,
+            total_consecutive_failures: 0,
+        };
+        tokio::spawn(buffer.run(receiver, flush_interval, error.clone()));
+
+        Self {
+            sender,
+            persister,
+            state,
+            error,
+        }
+    }
+
+    fn take_error(&self) -> StdResult<()> {
+        match self
+            .error
+            .lock()
+            .map_err(|_| anyhow!("Poisoned error lock"))?
+            .take()
+        {
+            Some(error) => Err(error.context("Failed to persist buffered repositories")),
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RepositoryPersister for BufferedPersister {
+    /// Buffers the repositories, waiting for room in the buffer if it is full.
+    async fn persist(&self, data: &[Repository]) -> StdResult<PersistReport> {
+        self.take_error()?;
+        self.state
+            .increment_total_pending_repositories(data.len() as u32)
+            .await;
+        for repository in data {
+            self.sender
+                .send(BufferMessage::Repository(Box::new(repository.clone())))
+                .await
+                .map_err(|_| anyhow!("Persister buffer is closed"))?;
+        }
+
+        Ok(PersistReport::default())
+    }
+
+    /// Persists the buffered repositories and waits for them to be committed.
+    async fn flush(&self) -> StdResult<()> {
+        self.take_error()?;
+        let (reply_sender, reply_receiver) = oneshot::channel();
+        self.sender
+            .send(BufferMessage::Flush(reply_sender))
+            .await
+            .map_err(|_| anyhow!("Persister buffer is closed"))?;
+
+        reply_receiver.await?
+    }
+
+    /// Marks the unreachable repositories with the underlying persister.
+    async fn mark_unreachable(&self, node_ids: &[String]) -> StdResult<u32> {
+        self.take_error()?;
+        self.persister.mark_unreachable(node_ids).await
+    }
+
+    /// Reads the known repositories with the underlying persister.
+    async fn read_known_repositories(&self, limit: u32) -> StdResult<Vec<Repository>> {
+        self.take_error()?;
+        self.persister.read_known_repositories(limit).await
+    }
+}
+
+/// The repositories buffered by the background task of a `BufferedPersister`.
+struct Buffer {
+    /// The persister to which the batches are written.
+    persister: Arc<dyn RepositoryPersister>,
+
+    /// The state of the crawler
+    state: Arc<CrawlerState>,
+
+    /// The buffered repositories.
+    repositories: Vec<Repository>,
+
+    /// The number of buffered repositories triggering a flush.
+    buffer_size: usize,
+
+    /// The number of consecutive failed flushes.
+    total_consecutive_failures: u32,
+}
+
+impl Buffer {
+    async fn run(
+        mut self,
+        mut receiver: mpsc::Receiver<BufferMessage>,
+        flush_interval: Duration,
+        error: Arc<std::sync::Mutex<Option<anyhow::Error>>>,
+    ) {
+        let mut ticker = interval(flush_interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            // The messages are left in the channel while a full buffer can not be persisted, so that the senders wait
+            let is_full = self.repositories.len() >= self.buffer_size;
+            let result = tokio::select! {
+                message = receiver.recv(), if !is_full => match message {
+                    Some(BufferMessage::Repository(repository)) => {
+                        self.repositories.push(*repository);
+                        if self.repositories.len() < self.buffer_size {
+                            continue;
+                        }
+                        self.flush().await
+                    }
+                    Some(BufferMessage::Flush(reply_sender)) => {
+                        let _ = reply_sender.send(self.flush().await);
+                        Ok(())
+                    }
+                    None => {
+                        if let Err(e) = self.flush().await {
+                            warn!("Failed to persist buffered repositories on shutdown: {e}");
+                        }
+                        return;
+                    }
+                },
+                _ = ticker.tick() => self.flush().await,
+            };
+            if let Err(e) = result {
+                warn!("Failed to persist buffered repositories: {e}");
+                if let Ok(mut error) = error.lock() {
+                    error.get_or_insert(e);
+                }
+            }
+            if self.total_consecutive_failures >= MAX_CONSECUTIVE_FLUSH_FAILURES {
+                error!(
+                    "Abandoning {} buffered repositories after {} consecutive failed flushes",
+                    self.repositories.len(),
+                    self.total_consecutive_failures
+                );
+                if let Ok(mut error) = error.lock() {
+                    error.get_or_insert(anyhow!(
+                        "Abandoned the buffered repositories after {} consecutive failed flushes",
+                        self.total_consecutive_failures
+                    ));
+                }
+                return;
+            }
+        }
+    }
+
+    /// Persists the buffered repositories, counting the consecutive failures.
+    async fn flush(&mut self) -> StdResult<()> {
+        let result = self.persist().await;
+        self.total_consecutive_failures = match result {
+            Ok(()) => 0,
+            Err(_) => self.total_consecutive_failures + 1,
+        };
+
+        result
+    }
+
+    /// Persists the buffered repositories and increments the totals of the crawler state.
+    ///
+    /// The repositories are kept in the buffer until they are persisted, so that a failed batch is retried by the next flush.
+    async fn persist(&mut self) -> StdResult<()> {
+        if self.repositories.is_empty() {
+            return Ok(());
+        }
+        let report = self.persister.persist(&self.repositories).await?;
+        self.state
+            .decrement_total_pending_repositories(self.repositories.len() as u32)
+            .await;
+        self.repositories.clear();
+        self.state
+            .increment_total_persisted_repositories(report.total_inserted)
+            .await;
+        self.state
+            .increment_total_collisions_repositories(report.total_collisions)
+            .await;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::function;
+
+    use super::*;
+    use crate::MockRepositoryPersister;
+
+    #[tokio::test]
+    async fn persist_in_batches_when_buffer_is_full() {
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            persister
+                .expect_persist()
+                .with(function(|repositories: &[Repository]| {
+                    repositories.len() == 2
+                }))
+                .returning(|_| Ok(PersistReport::new(2, 2)))
+                .times(1);
+            persister
+                .expect_persist()
+                .with(function(|repositories: &[Repository]| {
+                    repositories.len() == 1
+                }))
+                .returning(|_| Ok(PersistReport::new(1, 0)))
+                .times(1);
+
+            persister
+        };
+        let state = Arc::new(CrawlerState::default());
+        let buffered_persister = BufferedPersister::new(
+            Arc::new(persister),
+            state.clone(),
+            2,
+            Duration::from_secs(3600),
+        );
+
+        let report = buffered_persister
+            .persist(&[
+                Repository::new("repository-1", "org-1", 100),
+                Repository::new("repository-2", "org-1", 200),
+                Repository::new("repository-3", "org-1", 300),
+            ])
+            .await
+            .unwrap();
+        buffered_persister.flush().await.unwrap();
+
+        assert_eq!(PersistReport::default(), report);
+        assert_eq!(2, state.get_total_persisted_repositories().await);
+        assert_eq!(1, state.get_total_collisions_repositories().await);
+        assert_eq!(0, state.get_total_pending_repositories().await);
+    }
+
+    #[tokio::test]
+    async fn persist_when_flush_interval_elapses() {
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            persister
+                .expect_persist()
+                .returning(|_| Ok(PersistReport::new(1, 1)))
+                .times(1);
+
+            persister
+        };
+        let state = Arc::new(CrawlerState::default());
+        let buffered_persister = BufferedPersister::new(
+            Arc::new(persister),
+            state.clone(),
+            100,
+            Duration::from_millis(10),
+        );
+
+        buffered_persister
+            .persist(&[Repository::new("repository-1", "org-1", 100)])
+            .await
+            .unwrap();
+        assert_eq!(1, state.get_total_pending_repositories().await);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(1, state.get_total_persisted_repositories().await);
+        assert_eq!(0, state.get_total_pending_repositories().await);
+    }
+
+    #[tokio::test]
+    async fn flush_fails_if_persist_fails() {
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            let mut sequence = mockall::Sequence::new();
+            persister
+                .expect_persist()
+                .returning(|_| Err(anyhow!("Error persisting data")))
+                .times(1)
+                .in_sequence(&mut sequence);
+            persister
+                .expect_persist()
+                .withf(|repositories| repositories.len() == 1)
+                .returning(|repositories| Ok(PersistReport::new(repositories.len() as u32, 1)))
+                .times(1)
+                .in_sequence(&mut sequence);
+
+            persister
+        };
+        let state = Arc::new(CrawlerState::default());
+        let buffered_persister = BufferedPersister::new(
+            Arc::new(persister),
+            state.clone(),
+            100,
+            Duration::from_secs(3600),
+        );
+
+        buffered_persister
+            .persist(&[Repository::new("repository-1", "org-1", 100)])
+            .await
+            .unwrap();
+
+        buffered_persister
+            .flush()
+            .await
+            .expect_err("Flush should fail if persist fails");
+        assert_eq!(0, state.get_total_persisted_repositories().await);
+        assert_eq!(1, state.get_total_pending_repositories().await);
+
+        buffered_persister.flush().await.unwrap();
+        assert_eq!(1, state.get_total_persisted_repositories().await);
+        assert_eq!(0, state.get_total_pending_repositories().await);
+    }
+
+    #[tokio::test]
+    async fn abandon_buffer_after_consecutive_failed_flushes() {
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            persister
+                .expect_persist()
+                .withf(|repositories| repositories.len() == 1)
+                .returning(|_| Err(anyhow!("Error persisting data")))
+                .times(MAX_CONSECUTIVE_FLUSH_FAILURES as usize);
+
+            persister
+        };
+        let state = Arc::new(CrawlerState::default());
+        let buffered_persister = BufferedPersister::new(
+            Arc::new(persister),
+            state.clone(),
+            1,
+            Duration::from_millis(10),
+        );
+
+        buffered_persister
+            .persist(&[Repository::new("repository-1", "org-1", 100)])
+            .await
+            .unwrap();
+        // The repositories are no longer buffered while the full buffer can not be persisted
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            buffered_persister.persist(&[
+                Repository::new("repository-2", "org-1", 200),
+                Repository::new("repository-3", "org-1", 300),
+            ]),
+        )
+        .await
+        .expect("Persist should stop waiting once the buffer is abandoned")
+        .expect_err("Persist should fail once the buffer is abandoned");
+
+        buffered_persister
+            .flush()
+            .await
+            .expect_err("Flush should fail once the buffer is abandoned");
+        assert_eq!(0, state.get_total_persisted_repositories().await);
+    }
+}
@@ -0,0 +1,195 @@
+use std::{collections::HashSet, fs::OpenOptions, path::Path};
+
+use anyhow::{Context, anyhow, bail};
+use async_trait::async_trait;
+use clap::ValueEnum;
+use log::info;
+use tokio::{fs::File, io::AsyncWriteExt, sync::Mutex};
+
+use crate::{ExportColumn, PersistReport, Repository, RepositoryPersister, StdResult};
+
+/// The state of a CSV sink, guarded by a lock.
+struct CsvSink {
+    /// The keys (repository name, organization name) of the repositories already written.
+    persisted_keys: HashSet<(String, String)>,
+
+    /// The file, opened in append mode.
+    file: File,
+}
+
+/// A persister that appends repository metadata to a CSV file with a header row.
+///
+/// Repositories already written to the file (by name and organization) are skipped, and an existing file must have the
+/// header of the exported columns.
+pub struct CsvPersister {
+    sink: Mutex<CsvSink>,
+}
+
+impl CsvPersister {
+    /// Creates a new `CsvPersister` instance appending to the given file.
+    pub fn try_new(path: &Path) -> StdResult<Self> {
+        let is_empty = !path.exists() || std::fs::metadata(path)?.len() == 0;
+        let persisted_keys = match is_empty {
+            true => HashSet::new(),
+            false => Self::read_persisted_keys(path)?,
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open CSV file {path:?}"))?;
+        if is_empty {
+            let mut writer = csv::Writer::from_writer(&file);
+            writer.write_record(&Self::headers())?;
+            writer.flush()?;
+        }
+
+        Ok(Self {
+            sink: Mutex::new(CsvSink {
+                persisted_keys,
+                file: File::from_std(file),
+            }),
+        })
+    }
+
+    /// Retrieves the header of the file, with the names of the exported columns.
+    fn headers() -> csv::StringRecord {
+        ExportColumn::value_variants()
+            .iter()
+            .map(|column| column.name())
+            .collect()
+    }
+
+    fn read_persisted_keys(path: &Path) -> StdResult<HashSet<(String, String)>> {
+        let mut reader = csv::Reader::from_path(path)?;
+        let headers = reader.headers()?.clone();
+        if headers != Self::headers() {
+            bail!(
+                "Mismatched header in CSV file {path:?}: expected {:?}, found {:?}",
+                Self::headers().iter().collect::<Vec<_>>(),
+                headers.iter().collect::<Vec<_>>()
+            );
+        }
+        let column_index = |column: ExportColumn| {
+            headers
+                .iter()
+                .position(|header| header == column.name())
+                .ok_or_else(|| anyhow!("Missing column {} in CSV file {path:?}", column.name()))
+        };
+        let repository_name_index = column_index(ExportColumn::RepositoryName)?;
+        let organization_name_index = column_index(ExportColumn::OrganizationName)?;
+        let mut persisted_keys = HashSet::new();
+        for record in reader.records() {
+            let record = record?;
+            if let (Some(repository_name), Some(organization_name)) = (
+                record.get(repository_name_index),
+                record.get(organization_name_index),
+            ) {
+                persisted_keys.insert((repository_name.to_owned(), organization_name.to_owned()));
+            }
+        }
+
+        Ok(persisted_keys)
+    }
+}
+
+#[async_trait]
+impl RepositoryPersister for CsvPersister {
+    async fn persist(&self, data: &[Repository]) -> StdResult<PersistReport> {
+        let mut sink = self.sink.lock().await;
+        let mut inserted_keys = HashSet::new();
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for repository in data {
+            let key = (
+                repository.repository_name().to_string(),
+                repository.organization_name().to_string(),
+            );
+            if sink.persisted_keys.contains(&key) || inserted_keys.contains(&key) {
+                info!("Skipped already persisted {repository}");
+                continue;
+            }
+            writer.write_record(
+                ExportColumn::value_variants()
+                    .iter()
+                    .map(|column| column.value(repository).to_text()),
+            )?;
+            inserted_keys.insert(key);
+            info!("Inserted {repository}");
+        }
+        // The records are written to the file at once, without blocking the runtime
+        let records = writer
+            .into_inner()
+            .map_err(|error| anyhow!("Failed to write CSV records: {}", error.error()))?;
+        sink.file.write_all(&records).await?;
+        sink.file.flush().await?;
+        let total_inserted = inserted_keys.len() as u32;
+        sink.persisted_keys.extend(inserted_keys);
+
+        Ok(PersistReport::new(data.len() as u32, total_inserted))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn persist_skips_duplicates() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("repository.csv");
+        let persister = CsvPersister::try_new(&path).unwrap();
+
+        let report = persister
+            .persist(&[
+                Repository::new("repository-1", "org-1", 100),
+                Repository::new("repository-2", "org-1", 200),
+                Repository::new("repository-1", "org-1", 300),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(PersistReport::new(3, 2), report);
+        assert_eq!(3, std::fs::read_to_string(&path).unwrap().lines().count());
+    }
+
+    #[tokio::test]
+    async fn persist_skips_repositories_of_existing_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("repository.csv");
+        CsvPersister::try_new(&path)
+            .unwrap()
+            .persist(&[Repository::new("repository-1", "org-1", 100)])
+            .await
+            .unwrap();
+        let persister = CsvPersister::try_new(&path).unwrap();
+
+        let report = persister
+            .persist(&[
+                Repository::new("repository-1", "org-1", 100),
+                Repository::new("repository-2", "org-1", 200),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(PersistReport::new(2, 1), report);
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(3, content.lines().count());
+        assert!(content.starts_with("repository_name,organization_name,total_stars,"));
+    }
+
+    #[test]
+    fn try_new_fails_on_mismatched_header() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("repository.csv");
+        std::fs::write(
+            &path,
+            "repository_name,organization_name,total_stars\nrepository-1,org-1,100\n",
+        )
+        .unwrap();
+
+        let result = CsvPersister::try_new(&path);
+
+        assert!(result.is_err());
+        assert_eq!(2, std::fs::read_to_string(&path).unwrap().lines().count());
+    }
+}
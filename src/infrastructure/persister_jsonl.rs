@@ -0,0 +1,163 @@
+use std::{
+    collections::HashSet,
+    fs::OpenOptions,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use clap::ValueEnum;
+use log::info;
+use serde_json::{Map, Value};
+use tokio::{fs::File, io::AsyncWriteExt, sync::Mutex};
+
+use crate::{ExportColumn, PersistReport, Repository, RepositoryPersister, StdResult};
+
+/// The state of a JSON Lines sink, guarded by a lock.
+struct JsonLinesSink {
+    /// The keys (repository name, organization name) of the repositories already written.
+    persisted_keys: HashSet<(String, String)>,
+
+    /// The file, opened in append mode.
+    file: File,
+}
+
+/// A persister that appends repository metadata to a JSON Lines file, one JSON object per repository.
+///
+/// Repositories already written to the file (by name and organization) are skipped.
+pub struct JsonLinesPersister {
+    sink: Mutex<JsonLinesSink>,
+}
+
+impl JsonLinesPersister {
+    /// Creates a new `JsonLinesPersister` instance appending to the given file.
+    pub fn try_new(path: &Path) -> StdResult<Self> {
+        let persisted_keys = match path.exists() {
+            true => Self::read_persisted_keys(path)?,
+            false => HashSet::new(),
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open JSON Lines file {path:?}"))?;
+
+        Ok(Self {
+            sink: Mutex::new(JsonLinesSink {
+                persisted_keys,
+                file: File::from_std(file),
+            }),
+        })
+    }
+
+    fn read_persisted_keys(path: &Path) -> StdResult<HashSet<(String, String)>> {
+        let mut persisted_keys = HashSet::new();
+        for line in BufReader::new(std::fs::File::open(path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let object: Map<String, Value> = serde_json::from_str(&line)
+                .with_context(|| format!("Invalid line in JSON Lines file {path:?}: {line}"))?;
+            if let (Some(Value::String(repository_name)), Some(Value::String(organization_name))) = (
+                object.get(ExportColumn::RepositoryName.name()),
+                object.get(ExportColumn::OrganizationName.name()),
+            ) {
+                persisted_keys.insert((repository_name.to_owned(), organization_name.to_owned()));
+            }
+        }
+
+        Ok(persisted_keys)
+    }
+}
+
+#[async_trait]
+impl RepositoryPersister for JsonLinesPersister {
+    async fn persist(&self, data: &[Repository]) -> StdResult<PersistReport> {
+        let mut sink = self.sink.lock().await;
+        let mut inserted_keys = HashSet::new();
+        let mut lines = Vec::new();
+        for repository in data {
+            let key = (
+                repository.repository_name().to_string(),
+                repository.organization_name().to_string(),
+            );
+            if sink.persisted_keys.contains(&key) || inserted_keys.contains(&key) {
+                info!("Skipped already persisted {repository}");
+                continue;
+            }
+            let object = ExportColumn::value_variants()
+                .iter()
+                .map(|column| {
+                    (
+                        column.name().to_string(),
+                        column.value(repository).to_json(),
+                    )
+                })
+                .collect::<Map<String, Value>>();
+            serde_json::to_writer(&mut lines, &object)?;
+            lines.push(b'\n');
+            inserted_keys.insert(key);
+            info!("Inserted {repository}");
+        }
+        // The lines are written to the file at once, without blocking the runtime
+        sink.file.write_all(&lines).await?;
+        sink.file.flush().await?;
+        let total_inserted = inserted_keys.len() as u32;
+        sink.persisted_keys.extend(inserted_keys);
+
+        Ok(PersistReport::new(data.len() as u32, total_inserted))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn persist_skips_duplicates() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("repository.jsonl");
+        let persister = JsonLinesPersister::try_new(&path).unwrap();
+
+        let report = persister
+            .persist(&[
+                Repository::new("repository-1", "org-1", 100),
+                Repository::new("repository-2", "org-1", 200),
+                Repository::new("repository-1", "org-1", 300),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(PersistReport::new(3, 2), report);
+        assert_eq!(2, std::fs::read_to_string(&path).unwrap().lines().count());
+    }
+
+    #[tokio::test]
+    async fn persist_skips_repositories_of_existing_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("repository.jsonl");
+        JsonLinesPersister::try_new(&path)
+            .unwrap()
+            .persist(&[Repository::new("repository-1", "org-1", 100)])
+            .await
+            .unwrap();
+        let persister = JsonLinesPersister::try_new(&path).unwrap();
+
+        let report = persister
+            .persist(&[
+                Repository::new("repository-1", "org-1", 100),
+                Repository::new("repository-2", "org-1", 200),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(PersistReport::new(2, 1), report);
+        let lines = std::fs::read_to_string(&path).unwrap();
+        let first_object: Map<String, Value> =
+            serde_json::from_str(lines.lines().next().unwrap()).unwrap();
+        assert_eq!(2, lines.lines().count());
+        assert_eq!(Some(&Value::from(100)), first_object.get("total_stars"));
+    }
+}
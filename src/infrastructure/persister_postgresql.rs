@@ -1,71 +1,375 @@
-use std::ops::Deref;
+use std::{collections::HashMap, ops::Deref};
 
+use chrono::{DateTime, Utc};
 use log::info;
 use sqlx::{PgPool, postgres::PgPoolOptions};
 
-use crate::{Repository, RepositoryPersister, StdResult};
+use crate::{
+    POSTGRES_MIGRATOR, PersistReport, PersisterError, Repository, RepositoryPersister, StdResult,
+};
+
+const CLAIM_LEGACY_QUERY: &str = r#"
+UPDATE github.repository AS r
+SET node_id = i.node_id
+FROM UNNEST($1::TEXT[], $2::TEXT[], $3::TEXT[]) AS i (node_id, repository_name, organization_name)
+WHERE r.node_id IS NULL
+    AND r.repository_name = i.repository_name
+    AND r.organization_name = i.organization_name
+    AND NOT EXISTS (SELECT 1 FROM github.repository WHERE node_id = i.node_id);
+"#;
 
 const UPSERT_QUERY: &str = r#"
-WITH upserted AS (
-    INSERT INTO github.repository (repository_name, organization_name, total_stars)
-    VALUES ($1, $2, $3)
-    ON CONFLICT (repository_name, organization_name) DO UPDATE
-    SET total_stars = EXCLUDED.total_stars
-    WHERE github.repository.repository_name IS DISTINCT FROM EXCLUDED.repository_name
-    RETURNING xmax = 0 AS inserted
+WITH input AS (
+    SELECT *
+    FROM UNNEST(
+        $1::TEXT[], $2::TEXT[], $3::TEXT[], $4::INT[], $5::BIGINT[], $6::TEXT[], $7::INT[],
+        $8::BOOLEAN[], $9::BOOLEAN[], $10::TIMESTAMPTZ[], $11::TIMESTAMPTZ[], $12::TEXT[],
+        $13::TEXT[], $14::TEXT[], $15::TIMESTAMPTZ[], $16::TEXT[]
+    ) AS i (
+        node_id, repository_name, organization_name, total_stars,
+        database_id, primary_language, fork_count, is_fork, is_archived,
+        created_at, pushed_at, license, description, topics, observed_at, owner_type
+    )
+),
+previous AS (
+    SELECT r.id, r.node_id, r.repository_name, r.organization_name
+    FROM github.repository AS r
+    JOIN input USING (node_id)
+),
+upserted AS (
+    INSERT INTO github.repository AS r (
+        node_id, repository_name, organization_name, owner_type, total_stars,
+        database_id, primary_language, fork_count, is_fork, is_archived,
+        created_at, pushed_at, license, description, topics
+    )
+    SELECT node_id, repository_name, organization_name, owner_type, total_stars,
+        database_id, primary_language, fork_count, is_fork, is_archived,
+        created_at, pushed_at, license, description,
+        CASE WHEN topics IS NULL THEN NULL
+            ELSE ARRAY(SELECT jsonb_array_elements_text(topics::JSONB)) END
+    FROM input
+    ON CONFLICT (node_id) DO UPDATE
+    SET repository_name = EXCLUDED.repository_name,
+        organization_name = EXCLUDED.organization_name,
+        owner_type = COALESCE(EXCLUDED.owner_type, r.owner_type),
+        total_stars = EXCLUDED.total_stars,
+        database_id = COALESCE(EXCLUDED.database_id, r.database_id),
+        primary_language = COALESCE(EXCLUDED.primary_language, r.primary_language),
+        fork_count = COALESCE(EXCLUDED.fork_count, r.fork_count),
+        is_fork = COALESCE(EXCLUDED.is_fork, r.is_fork),
+        is_archived = COALESCE(EXCLUDED.is_archived, r.is_archived),
+        created_at = COALESCE(EXCLUDED.created_at, r.created_at),
+        pushed_at = COALESCE(EXCLUDED.pushed_at, r.pushed_at),
+        license = COALESCE(EXCLUDED.license, r.license),
+        description = COALESCE(EXCLUDED.description, r.description),
+        topics = COALESCE(EXCLUDED.topics, r.topics),
+        unreachable_at = NULL
+    WHERE (
+        r.repository_name, r.organization_name, r.owner_type, r.total_stars, r.database_id,
+        r.primary_language, r.fork_count, r.is_fork, r.is_archived, r.created_at,
+        r.pushed_at, r.license, r.description, r.topics, r.unreachable_at
+    ) IS DISTINCT FROM (
+        EXCLUDED.repository_name,
+        EXCLUDED.organization_name,
+        COALESCE(EXCLUDED.owner_type, r.owner_type),
+        EXCLUDED.total_stars,
+        COALESCE(EXCLUDED.database_id, r.database_id),
+        COALESCE(EXCLUDED.primary_language, r.primary_language),
+        COALESCE(EXCLUDED.fork_count, r.fork_count),
+        COALESCE(EXCLUDED.is_fork, r.is_fork),
+        COALESCE(EXCLUDED.is_archived, r.is_archived),
+        COALESCE(EXCLUDED.created_at, r.created_at),
+        COALESCE(EXCLUDED.pushed_at, r.pushed_at),
+        COALESCE(EXCLUDED.license, r.license),
+        COALESCE(EXCLUDED.description, r.description),
+        COALESCE(EXCLUDED.topics, r.topics),
+        NULL::TIMESTAMPTZ
+    )
+    RETURNING r.node_id, xmax = 0 AS inserted
+),
+renamed AS (
+    INSERT INTO github.repository_rename (
+        repository_id, previous_repository_name, previous_organization_name,
+        repository_name, organization_name, renamed_at
+    )
+    SELECT p.id, p.repository_name, p.organization_name,
+        i.repository_name, i.organization_name, i.observed_at
+    FROM previous AS p
+    JOIN input AS i USING (node_id)
+    WHERE (p.repository_name, p.organization_name)
+        IS DISTINCT FROM (i.repository_name, i.organization_name)
 )
-SELECT COUNT(*) AS total_inserted
+SELECT node_id
 FROM upserted
 WHERE inserted = true;
 "#;
 
+const SNAPSHOT_QUERY: &str = r#"
+INSERT INTO github.repository_stars_snapshot (repository_id, crawled_at, observed_at, total_stars)
+SELECT r.id, $2, i.observed_at, i.total_stars
+FROM UNNEST($1::TEXT[], $3::TIMESTAMPTZ[], $4::INT[]) AS i (node_id, observed_at, total_stars)
+JOIN github.repository AS r USING (node_id)
+ON CONFLICT (repository_id, crawled_at) DO UPDATE
+SET observed_at = EXCLUDED.observed_at, total_stars = EXCLUDED.total_stars;
+"#;
+
+const MARK_UNREACHABLE_QUERY: &str = r#"
+UPDATE github.repository
+SET unreachable_at = $2
+WHERE node_id = ANY($1::TEXT[]) AND unreachable_at IS NULL;
+"#;
+
+const SELECT_KNOWN_QUERY: &str = r#"
+SELECT node_id, repository_name, organization_name, total_stars
+FROM github.repository
+WHERE unreachable_at IS NULL
+ORDER BY id
+LIMIT $1;
+"#;
+
 /// A persister that stores repository metadata in a PostgreSQL database.
+///
+/// Each slice of repositories is persisted with multi-row statements inside a single transaction.
 pub struct PostgresSqlPersister {
     pool: PgPool,
+
+    /// The time at which the crawl run started, used to key the stars snapshots.
+    crawled_at: DateTime<Utc>,
+}
+
+/// The columns of a slice of repositories, bound as arrays to the multi-row statements.
+#[derive(Default)]
+struct RepositoryColumns {
+    node_id: Vec<String>,
+    repository_name: Vec<String>,
+    organization_name: Vec<String>,
+    owner_type: Vec<Option<String>>,
+    total_stars: Vec<i32>,
+    database_id: Vec<Option<i64>>,
+    primary_language: Vec<Option<String>>,
+    fork_count: Vec<Option<i32>>,
+    is_fork: Vec<Option<bool>>,
+    is_archived: Vec<Option<bool>>,
+    created_at: Vec<Option<DateTime<Utc>>>,
+    pushed_at: Vec<Option<DateTime<Utc>>>,
+    license: Vec<Option<String>>,
+    description: Vec<Option<String>>,
+    topics: Vec<Option<String>>,
+    observed_at: Vec<DateTime<Utc>>,
+}
+
+impl RepositoryColumns {
+    /// Builds the columns of the repositories, keeping only the last occurrence of a node identifier.
+    fn try_from_repositories(repositories: &[Repository]) -> StdResult<Self> {
+        let mut latest_repositories: HashMap<&str, &Repository> = HashMap::new();
+        let mut node_ids = Vec::new();
+        for repository in repositories {
+            let node_id = repository
+                .node_id()
+                .ok_or_else(|| {
+                    PersisterError::Constraint(format!("Missing node identifier for {repository}"))
+                })?
+                .deref()
+                .as_str();
+            if latest_repositories.insert(node_id, repository).is_none() {
+                node_ids.push(node_id);
+            }
+        }
+
+        let mut columns = Self::default();
+        for node_id in node_ids {
+            let repository = latest_repositories[node_id];
+            let metadata = repository.metadata();
+            columns.node_id.push(node_id.to_owned());
+            columns
+                .repository_name
+                .push(repository.repository_name().to_string());
+            columns
+                .organization_name
+                .push(repository.organization_name().to_string());
+            columns.owner_type.push(
+                repository
+                    .owner_type()
+                    .map(|owner_type| owner_type.name().to_owned()),
+            );
+            columns
+                .total_stars
+                .push(*repository.total_stars().deref() as i32);
+            columns.database_id.push(metadata.database_id);
+            columns
+                .primary_language
+                .push(metadata.primary_language.to_owned());
+            columns
+                .fork_count
+                .push(metadata.fork_count.map(|fork_count| fork_count as i32));
+            columns.is_fork.push(metadata.is_fork);
+            columns.is_archived.push(metadata.is_archived);
+            columns.created_at.push(metadata.created_at);
+            columns.pushed_at.push(metadata.pushed_at);
+            columns.license.push(metadata.license.to_owned());
+            columns.description.push(metadata.description.to_owned());
+            columns.topics.push(
+                metadata
+                    .topics
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+            );
+            columns
+                .observed_at
+                .push(repository.observed_at().unwrap_or_else(Utc::now));
+        }
+
+        Ok(columns)
+    }
 }
 
 impl PostgresSqlPersister {
-    /// Creates a new `PostgresSqlPersister` instance.
-    pub async fn try_new(connection_string: &str) -> StdResult<Self> {
-        Ok(Self {
-            pool: PgPoolOptions::new()
-                .max_connections(1)
-                .connect(connection_string)
-                .await?,
-        })
+    /// Creates a new `PostgresSqlPersister` instance, applying the pending migrations of the schema if `auto_migrate` is set.
+    pub async fn try_new(
+        connection_string: &str,
+        crawled_at: DateTime<Utc>,
+        auto_migrate: bool,
+    ) -> StdResult<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(1)
+            .connect(connection_string)
+            .await?;
+        if auto_migrate {
+            POSTGRES_MIGRATOR.run(&pool).await?;
+        }
+
+        Ok(Self { pool, crawled_at })
     }
 
-    async fn persist_repository(&self, repository: &Repository) -> StdResult<u32> {
+    async fn persist_repositories(
+        &self,
+        columns: RepositoryColumns,
+    ) -> Result<Vec<String>, PersisterError> {
         let mut transaction = self.pool.begin().await?;
-        let repository_name = &*repository.repository_name().deref();
-        let organization_name = &*repository.organization_name().deref();
-        let repository_stars = *repository.total_stars().deref() as i32;
-        let row: (i64,) = sqlx::query_as(UPSERT_QUERY)
-            .bind(repository_name.to_owned())
-            .bind(organization_name.to_owned())
-            .bind(repository_stars)
-            .fetch_one(&mut *transaction)
+        sqlx::query(CLAIM_LEGACY_QUERY)
+            .bind(&columns.node_id)
+            .bind(&columns.repository_name)
+            .bind(&columns.organization_name)
+            .execute(&mut *transaction)
+            .await?;
+        let inserted_node_ids: Vec<(String,)> = sqlx::query_as(UPSERT_QUERY)
+            .bind(&columns.node_id)
+            .bind(&columns.repository_name)
+            .bind(&columns.organization_name)
+            .bind(&columns.total_stars)
+            .bind(&columns.database_id)
+            .bind(&columns.primary_language)
+            .bind(&columns.fork_count)
+            .bind(&columns.is_fork)
+            .bind(&columns.is_archived)
+            .bind(&columns.created_at)
+            .bind(&columns.pushed_at)
+            .bind(&columns.license)
+            .bind(&columns.description)
+            .bind(&columns.topics)
+            .bind(&columns.observed_at)
+            .bind(&columns.owner_type)
+            .fetch_all(&mut *transaction)
+            .await?;
+        sqlx::query(SNAPSHOT_QUERY)
+            .bind(&columns.node_id)
+            .bind(self.crawled_at)
+            .bind(&columns.observed_at)
+            .bind(&columns.total_stars)
+            .execute(&mut *transaction)
             .await?;
         transaction.commit().await?;
 
-        Ok(row.0 as u32)
+        Ok(inserted_node_ids
+            .into_iter()
+            .map(|(node_id,)| node_id)
+            .collect())
     }
 }
 
 #[async_trait::async_trait]
 impl RepositoryPersister for PostgresSqlPersister {
-    async fn persist(&self, data: &[Repository]) -> StdResult<u32> {
-        let mut total_inserted = 0;
+    async fn persist(&self, data: &[Repository]) -> StdResult<PersistReport> {
+        if data.is_empty() {
+            return Ok(PersistReport::default());
+        }
+        let columns = RepositoryColumns::try_from_repositories(data)?;
+        let inserted_node_ids = self.persist_repositories(columns).await?;
         for repository in data {
-            let inserted_rows = self.persist_repository(repository).await?;
-            if inserted_rows == 0 {
-                info!("Updated {repository}");
-            } else {
-                info!("Inserted {repository}");
+            match repository
+                .node_id()
+                .is_some_and(|node_id| inserted_node_ids.contains(node_id))
+            {
+                true => info!("Inserted {repository}"),
+                false => info!("Updated {repository}"),
             }
-            total_inserted += inserted_rows;
         }
 
-        Ok(total_inserted)
+        Ok(PersistReport::new(
+            data.len() as u32,
+            inserted_node_ids.len() as u32,
+        ))
+    }
+
+    async fn mark_unreachable(&self, node_ids: &[String]) -> StdResult<u32> {
+        let result = sqlx::query(MARK_UNREACHABLE_QUERY)
+            .bind(node_ids)
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await
+            .map_err(PersisterError::from)?;
+
+        Ok(result.rows_affected() as u32)
+    }
+
+    async fn read_known_repositories(&self, limit: u32) -> StdResult<Vec<Repository>> {
+        let rows: Vec<(Option<String>, String, String, i32)> = sqlx::query_as(SELECT_KNOWN_QUERY)
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(PersisterError::from)?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(node_id, repository_name, organization_name, total_stars)| {
+                    let repository =
+                        Repository::new(&repository_name, &organization_name, total_stars as u32);
+                    match node_id {
+                        Some(node_id) => repository.with_node_id(&node_id),
+                        None => repository,
+                    }
+                },
+            )
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repository_columns_keep_last_occurrence_of_node_id() {
+        let repositories = vec![
+            Repository::new("repository-1", "org-1", 100).with_node_id("R_1"),
+            Repository::new("repository-2", "org-1", 200).with_node_id("R_2"),
+            Repository::new("repository-1", "org-1", 150).with_node_id("R_1"),
+        ];
+
+        let columns = RepositoryColumns::try_from_repositories(&repositories).unwrap();
+
+        assert_eq!(vec!["R_1", "R_2"], columns.node_id);
+        assert_eq!(vec![150, 200], columns.total_stars);
+    }
+
+    #[test]
+    fn repository_columns_fail_without_node_id() {
+        let repositories = vec![Repository::new("repository-1", "org-1", 100)];
+
+        let result = RepositoryColumns::try_from_repositories(&repositories);
+
+        assert!(result.is_err(), "Should fail without node identifier");
     }
 }
@@ -1,12 +1,13 @@
 use std::{sync::Arc, time::Duration};
 
-use anyhow::anyhow;
 use log::warn;
 use tokio::time::sleep;
 
-use crate::{Repository, RepositoryPersister, StdResult};
+use crate::{CrawlerState, PersistReport, Repository, RepositoryPersister, RetryPolicy, StdResult};
 
 /// A struct that retries a RepositoryPersister a specified number of times in case of failure with exponential backoff strategy.
+///
+/// Permanent errors, such as constraint violations, fail fast.
 pub struct PersisterRetrier {
     /// The persister to be retried.
     persister: Arc<dyn RepositoryPersister>,
@@ -14,6 +15,8 @@ pub struct PersisterRetrier {
     max_retries: u32,
     /// The base delay for exponential backoff.
     base_delay: Duration,
+    /// The state of the crawler
+    state: Arc<CrawlerState>,
 }
 
 impl PersisterRetrier {
@@ -22,11 +25,13 @@ impl PersisterRetrier {
         persister: Arc<dyn RepositoryPersister>,
         max_retries: u32,
         base_delay: Duration,
+        state: Arc<CrawlerState>,
     ) -> Self {
         Self {
             persister,
             max_retries,
             base_delay,
+            state,
         }
     }
 
@@ -38,7 +43,7 @@ impl PersisterRetrier {
 #[async_trait::async_trait]
 impl RepositoryPersister for PersisterRetrier {
     /// Retries the persist operation if it fails, up to the maximum number of retries.
-    async fn persist(&self, repositories: &[Repository]) -> StdResult<u32> {
+    async fn persist(&self, repositories: &[Repository]) -> StdResult<PersistReport> {
         let mut attempts = 0;
 
         loop {
@@ -47,36 +52,65 @@ impl RepositoryPersister for PersisterRetrier {
                 Err(e) => {
                     warn!("Persist attempt #{} failed: {}", attempts + 1, e);
                     attempts += 1;
+                    if RetryPolicy::of(&e) == RetryPolicy::FailFast {
+                        return Err(e.context("Persist failed with a permanent error"));
+                    }
                     if attempts >= self.max_retries {
-                        return Err(anyhow!("Failed after {} attempts: {}", attempts, e));
+                        return Err(e.context(format!("Failed after {attempts} attempts")));
                     }
+                    self.state.increment_total_persister_retries(1).await;
                     sleep(self.calculate_exponential_backoff_delay(attempts)).await;
                 }
             }
         }
     }
+
+    /// Flushes the underlying persister.
+    async fn flush(&self) -> StdResult<()> {
+        self.persister.flush().await
+    }
+
+    /// Marks the unreachable repositories with the underlying persister.
+    async fn mark_unreachable(&self, node_ids: &[String]) -> StdResult<u32> {
+        self.persister.mark_unreachable(node_ids).await
+    }
+
+    /// Reads the known repositories with the underlying persister.
+    async fn read_known_repositories(&self, limit: u32) -> StdResult<Vec<Repository>> {
+        self.persister.read_known_repositories(limit).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use anyhow::anyhow;
+
     use super::*;
-    use crate::{MockRepositoryPersister, Repository};
+    use crate::{MockRepositoryPersister, PersisterError, Repository};
     use std::sync::Arc;
 
     #[tokio::test]
     async fn persist_success_on_first_attempt() {
         let persister = {
             let mut persister = MockRepositoryPersister::new();
-            persister.expect_persist().returning(|_| Ok(10)).times(1);
+            persister
+                .expect_persist()
+                .returning(|_| Ok(PersistReport::new(1, 1)))
+                .times(1);
 
             persister
         };
-        let retrier = PersisterRetrier::new(Arc::new(persister), 3, Duration::from_millis(10));
+        let retrier = PersisterRetrier::new(
+            Arc::new(persister),
+            3,
+            Duration::from_millis(10),
+            Arc::new(CrawlerState::default()),
+        );
 
         let result = retrier
             .persist(&[Repository::new("repository-1", "org-1", 100)])
             .await;
-        assert_eq!(result.unwrap(), 10);
+        assert_eq!(result.unwrap(), PersistReport::new(1, 1));
     }
 
     #[tokio::test]
@@ -87,17 +121,27 @@ mod tests {
                 .expect_persist()
                 .returning(|_| Err(anyhow!("Temporary failure")))
                 .times(2);
-            persister.expect_persist().returning(|_| Ok(10)).times(1);
+            persister
+                .expect_persist()
+                .returning(|_| Ok(PersistReport::new(1, 1)))
+                .times(1);
 
             persister
         };
-        let retrier = PersisterRetrier::new(Arc::new(persister), 3, Duration::from_millis(10));
+        let state = Arc::new(CrawlerState::default());
+        let retrier = PersisterRetrier::new(
+            Arc::new(persister),
+            3,
+            Duration::from_millis(10),
+            state.clone(),
+        );
 
         let result = retrier
             .persist(&[Repository::new("repository-1", "org-1", 100)])
             .await
             .unwrap();
-        assert_eq!(result, 10);
+        assert_eq!(result, PersistReport::new(1, 1));
+        assert_eq!(2, state.get_total_persister_retries().await);
     }
 
     #[tokio::test]
@@ -106,16 +150,59 @@ mod tests {
             let mut persister = MockRepositoryPersister::new();
             persister
                 .expect_persist()
-                .returning(|_| Err(anyhow!("Temporary failure")))
+                .returning(|_| {
+                    Err(anyhow!(PersisterError::Connection(
+                        "Connection refused".to_string()
+                    )))
+                })
                 .times(3);
 
             persister
         };
-        let retrier = PersisterRetrier::new(Arc::new(persister), 3, Duration::from_millis(10));
+        let retrier = PersisterRetrier::new(
+            Arc::new(persister),
+            3,
+            Duration::from_millis(10),
+            Arc::new(CrawlerState::default()),
+        );
+
+        let error = retrier
+            .persist(&[Repository::new("repository-1", "org-1", 100)])
+            .await
+            .expect_err("Should retrurn an error after max retries");
+        assert!(matches!(
+            error.downcast_ref::<PersisterError>(),
+            Some(PersisterError::Connection(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn persist_fails_fast_on_permanent_error() {
+        let persister = {
+            let mut persister = MockRepositoryPersister::new();
+            persister
+                .expect_persist()
+                .returning(|_| {
+                    Err(anyhow!(PersisterError::Constraint(
+                        "Missing node identifier".to_string()
+                    )))
+                })
+                .times(1);
+
+            persister
+        };
+        let state = Arc::new(CrawlerState::default());
+        let retrier = PersisterRetrier::new(
+            Arc::new(persister),
+            3,
+            Duration::from_secs(3600),
+            state.clone(),
+        );
 
         retrier
             .persist(&[Repository::new("repository-1", "org-1", 100)])
             .await
-            .expect_err("Should retrurn an error after max retries");
+            .expect_err("Should fail fast on a permanent error");
+        assert_eq!(0, state.get_total_persister_retries().await);
     }
 }
@@ -0,0 +1,512 @@
+use std::{ops::Deref, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use log::info;
+use sqlx::{
+    SqliteConnection, SqlitePool,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+
+use crate::{
+    PersistReport, PersisterError, Repository, RepositoryPersister, SQLITE_MIGRATOR, StdResult,
+};
+
+const SELECT_PREVIOUS_QUERY: &str = r#"
+SELECT id, repository_name, organization_name
+FROM repository
+WHERE node_id = $1;
+"#;
+
+const INSERT_QUERY: &str = r#"
+INSERT INTO repository (
+    node_id, repository_name, organization_name, total_stars,
+    database_id, primary_language, fork_count, is_fork, is_archived,
+    created_at, pushed_at, license, description, topics, owner_type
+)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15);
+"#;
+
+const UPDATE_QUERY: &str = r#"
+UPDATE repository
+SET repository_name = $2,
+    organization_name = $3,
+    total_stars = $4,
+    database_id = COALESCE($5, database_id),
+    primary_language = COALESCE($6, primary_language),
+    fork_count = COALESCE($7, fork_count),
+    is_fork = COALESCE($8, is_fork),
+    is_archived = COALESCE($9, is_archived),
+    created_at = COALESCE($10, created_at),
+    pushed_at = COALESCE($11, pushed_at),
+    license = COALESCE($12, license),
+    description = COALESCE($13, description),
+    topics = COALESCE($14, topics),
+    owner_type = COALESCE($15, owner_type),
+    unreachable_at = NULL
+WHERE node_id = $1;
+"#;
+
+const RENAME_QUERY: &str = r#"
+INSERT INTO repository_rename (
+    repository_id, previous_repository_name, previous_organization_name,
+    repository_name, organization_name, renamed_at
+)
+VALUES ($1, $2, $3, $4, $5, $6);
+"#;
+
+const SNAPSHOT_QUERY: &str = r#"
+INSERT INTO repository_stars_snapshot (repository_id, crawled_at, observed_at, total_stars)
+SELECT id, $2, $3, $4
+FROM repository
+WHERE node_id = $1
+ON CONFLICT (repository_id, crawled_at) DO UPDATE
+SET observed_at = excluded.observed_at, total_stars = excluded.total_stars;
+"#;
+
+const MARK_UNREACHABLE_QUERY: &str = r#"
+UPDATE repository
+SET unreachable_at = $2
+WHERE node_id = $1 AND unreachable_at IS NULL;
+"#;
+
+const SELECT_KNOWN_QUERY: &str = r#"
+SELECT node_id, repository_name, organization_name, total_stars
+FROM repository
+WHERE unreachable_at IS NULL
+ORDER BY id
+LIMIT $1;
+"#;
+
+/// A persister that stores repository metadata in an embedded SQLite database.
+///
+/// Each slice of repositories is persisted inside a single transaction.
+pub struct SqlitePersister {
+    pool: SqlitePool,
+
+    /// The time at which the crawl run started, used to key the stars snapshots.
+    crawled_at: DateTime<Utc>,
+}
+
+impl SqlitePersister {
+    /// Creates a new `SqlitePersister` instance, creating the database if needed and migrating its schema.
+    pub async fn try_new(connection_string: &str, crawled_at: DateTime<Utc>) -> StdResult<Self> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::from_str(connection_string)?
+                    .create_if_missing(true)
+                    .foreign_keys(true),
+            )
+            .await?;
+        SQLITE_MIGRATOR.run(&pool).await?;
+
+        Ok(Self { pool, crawled_at })
+    }
+
+    async fn persist_repository(
+        &self,
+        connection: &mut SqliteConnection,
+        repository: &Repository,
+    ) -> Result<u32, PersisterError> {
+        let node_id = repository
+            .node_id()
+            .ok_or_else(|| {
+                PersisterError::Constraint(format!("Missing node identifier for {repository}"))
+            })?
+            .deref();
+        let repository_name = repository.repository_name().deref();
+        let organization_name = repository.organization_name().deref();
+        let repository_stars = *repository.total_stars().deref() as i64;
+        let observed_at = repository.observed_at().unwrap_or_else(Utc::now);
+        let metadata = repository.metadata();
+        let topics = metadata
+            .topics
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| PersisterError::Query(e.to_string()))?;
+        let previous: Option<(i64, String, String)> = sqlx::query_as(SELECT_PREVIOUS_QUERY)
+            .bind(node_id)
+            .fetch_optional(&mut *connection)
+            .await?;
+        let upsert_query = match previous {
+            Some(_) => UPDATE_QUERY,
+            None => INSERT_QUERY,
+        };
+        sqlx::query(upsert_query)
+            .bind(node_id)
+            .bind(repository_name)
+            .bind(organization_name)
+            .bind(repository_stars)
+            .bind(metadata.database_id)
+            .bind(metadata.primary_language.as_deref())
+            .bind(metadata.fork_count.map(i64::from))
+            .bind(metadata.is_fork)
+            .bind(metadata.is_archived)
+            .bind(metadata.created_at)
+            .bind(metadata.pushed_at)
+            .bind(metadata.license.as_deref())
+            .bind(metadata.description.as_deref())
+            .bind(topics)
+            .bind(repository.owner_type().map(|owner_type| owner_type.name()))
+            .execute(&mut *connection)
+            .await?;
+        if let Some((repository_id, previous_repository_name, previous_organization_name)) =
+            &previous
+            && (
+                previous_repository_name.as_str(),
+                previous_organization_name.as_str(),
+            ) != (repository_name, organization_name)
+        {
+            sqlx::query(RENAME_QUERY)
+                .bind(repository_id)
+                .bind(previous_repository_name)
+                .bind(previous_organization_name)
+                .bind(repository_name)
+                .bind(organization_name)
+                .bind(observed_at)
+                .execute(&mut *connection)
+                .await?;
+        }
+        sqlx::query(SNAPSHOT_QUERY)
+            .bind(node_id)
+            .bind(self.crawled_at)
+            .bind(observed_at)
+            .bind(repository_stars)
+            .execute(&mut *connection)
+            .await?;
+
+        Ok(match previous {
+            Some(_) => 0,
+            None => 1,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl RepositoryPersister for SqlitePersister {
+    async fn persist(&self, data: &[Repository]) -> StdResult<PersistReport> {
+        let mut transaction = self.pool.begin().await.map_err(PersisterError::from)?;
+        let mut inserted_rows = Vec::with_capacity(data.len());
+        for repository in data {
+            inserted_rows.push(
+                self.persist_repository(&mut transaction, repository)
+                    .await?,
+            );
+        }
+        transaction.commit().await.map_err(PersisterError::from)?;
+        for (repository, inserted_rows) in data.iter().zip(&inserted_rows) {
+            if *inserted_rows == 0 {
+                info!("Updated {repository}");
+            } else {
+                info!("Inserted {repository}");
+            }
+        }
+        let total_inserted = inserted_rows.iter().sum();
+
+        Ok(PersistReport::new(data.len() as u32, total_inserted))
+    }
+
+    async fn mark_unreachable(&self, node_ids: &[String]) -> StdResult<u32> {
+        let unreachable_at = Utc::now();
+        let mut total_marked = 0;
+        let mut transaction = self.pool.begin().await.map_err(PersisterError::from)?;
+        for node_id in node_ids {
+            let result = sqlx::query(MARK_UNREACHABLE_QUERY)
+                .bind(node_id)
+                .bind(unreachable_at)
+                .execute(&mut *transaction)
+                .await
+                .map_err(PersisterError::from)?;
+            total_marked += result.rows_affected() as u32;
+        }
+        transaction.commit().await.map_err(PersisterError::from)?;
+
+        Ok(total_marked)
+    }
+
+    async fn read_known_repositories(&self, limit: u32) -> StdResult<Vec<Repository>> {
+        let rows: Vec<(String, String, String, i64)> = sqlx::query_as(SELECT_KNOWN_QUERY)
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(PersisterError::from)?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(node_id, repository_name, organization_name, total_stars)| {
+                    Repository::new(&repository_name, &organization_name, total_stars as u32)
+                        .with_node_id(&node_id)
+                },
+            )
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{OwnerType, RepositoryMetadata};
+
+    use super::*;
+
+    async fn setup_persister() -> SqlitePersister {
+        SqlitePersister::try_new("sqlite::memory:", Utc::now())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn persist_counts_inserted_repositories() {
+        let persister = setup_persister().await;
+
+        let report = persister
+            .persist(&[
+                Repository::new("repository-1", "org-1", 100).with_node_id("R_1"),
+                Repository::new("repository-2", "org-1", 200).with_node_id("R_2"),
+            ])
+            .await
+            .unwrap();
+        let report_again = persister
+            .persist(&[
+                Repository::new("repository-1", "org-1", 150).with_node_id("R_1"),
+                Repository::new("repository-3", "org-1", 300).with_node_id("R_3"),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(PersistReport::new(2, 2), report);
+        assert_eq!(PersistReport::new(2, 1), report_again);
+        let stars: (i64,) =
+            sqlx::query_as("SELECT total_stars FROM repository WHERE node_id = 'R_1'")
+                .fetch_one(&persister.pool)
+                .await
+                .unwrap();
+        assert_eq!(150, stars.0);
+    }
+
+    #[tokio::test]
+    async fn persist_keeps_metadata_not_fetched() {
+        let persister = setup_persister().await;
+
+        persister
+            .persist(&[Repository::new("repository-1", "org-1", 100)
+                .with_node_id("R_1")
+                .with_owner_type(OwnerType::User)
+                .with_metadata(RepositoryMetadata {
+                    license: Some("MIT".to_string()),
+                    topics: Some(vec!["rust".to_string()]),
+                    ..RepositoryMetadata::default()
+                })])
+            .await
+            .unwrap();
+        persister
+            .persist(&[Repository::new("repository-1", "org-1", 100).with_node_id("R_1")])
+            .await
+            .unwrap();
+
+        let row: (Option<String>, Option<String>, Option<String>) = sqlx::query_as(
+            "SELECT license, topics, owner_type FROM repository WHERE node_id = 'R_1'",
+        )
+        .fetch_one(&persister.pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            (
+                Some("MIT".to_string()),
+                Some(r#"["rust"]"#.to_string()),
+                Some("user".to_string())
+            ),
+            row
+        );
+    }
+
+    #[tokio::test]
+    async fn persist_records_renames_and_snapshots() {
+        let persister = setup_persister().await;
+
+        persister
+            .persist(&[Repository::new("repository-1", "org-1", 100).with_node_id("R_1")])
+            .await
+            .unwrap();
+        persister
+            .persist(&[Repository::new("repository-renamed", "org-2", 110).with_node_id("R_1")])
+            .await
+            .unwrap();
+
+        let rename: (String, String, String, String) = sqlx::query_as(
+            "SELECT previous_repository_name, previous_organization_name, repository_name, organization_name FROM repository_rename",
+        )
+        .fetch_one(&persister.pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            (
+                "repository-1".to_string(),
+                "org-1".to_string(),
+                "repository-renamed".to_string(),
+                "org-2".to_string()
+            ),
+            rename
+        );
+        let snapshot: (i64, i64) =
+            sqlx::query_as("SELECT COUNT(*), MAX(total_stars) FROM repository_stars_snapshot")
+                .fetch_one(&persister.pool)
+                .await
+                .unwrap();
+        assert_eq!((1, 110), snapshot);
+    }
+
+    #[tokio::test]
+    async fn mark_unreachable_until_persisted_again() {
+        let persister = setup_persister().await;
+        persister
+            .persist(&[
+                Repository::new("repository-1", "org-1", 100).with_node_id("R_1"),
+                Repository::new("repository-2", "org-1", 200).with_node_id("R_2"),
+            ])
+            .await
+            .unwrap();
+        let count_unreachable = || async {
+            let count: (i64,) =
+                sqlx::query_as("SELECT COUNT(*) FROM repository WHERE unreachable_at IS NOT NULL")
+                    .fetch_one(&persister.pool)
+                    .await
+                    .unwrap();
+            count.0
+        };
+
+        let total_marked = persister
+            .mark_unreachable(&["R_1".to_string(), "R_3".to_string()])
+            .await
+            .unwrap();
+        let total_unreachable = count_unreachable().await;
+        persister
+            .persist(&[Repository::new("repository-1", "org-1", 100).with_node_id("R_1")])
+            .await
+            .unwrap();
+
+        assert_eq!(1, total_marked);
+        assert_eq!(1, total_unreachable);
+        assert_eq!(0, count_unreachable().await);
+    }
+
+    #[tokio::test]
+    async fn read_known_repositories_skips_unreachable() {
+        let persister = setup_persister().await;
+        persister
+            .persist(&[
+                Repository::new("repository-1", "org-1", 100).with_node_id("R_1"),
+                Repository::new("repository-2", "org-1", 200).with_node_id("R_2"),
+                Repository::new("repository-3", "org-1", 300).with_node_id("R_3"),
+            ])
+            .await
+            .unwrap();
+        persister
+            .mark_unreachable(&["R_2".to_string()])
+            .await
+            .unwrap();
+
+        let repositories = persister.read_known_repositories(10).await.unwrap();
+        let limited_repositories = persister.read_known_repositories(1).await.unwrap();
+
+        assert_eq!(
+            vec![
+                Repository::new("repository-1", "org-1", 100).with_node_id("R_1"),
+                Repository::new("repository-3", "org-1", 300).with_node_id("R_3"),
+            ],
+            repositories
+        );
+        assert_eq!(
+            vec![Repository::new("repository-1", "org-1", 100).with_node_id("R_1")],
+            limited_repositories
+        );
+    }
+
+    #[tokio::test]
+    async fn persist_fails_without_node_id() {
+        let persister = setup_persister().await;
+
+        let error = persister
+            .persist(&[
+                Repository::new("repository-1", "org-1", 100).with_node_id("R_1"),
+                Repository::new("repository-2", "org-1", 200),
+            ])
+            .await
+            .expect_err("Should fail without node identifier");
+
+        assert!(matches!(
+            error.downcast_ref::<PersisterError>(),
+            Some(PersisterError::Constraint(_))
+        ));
+        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM repository")
+            .fetch_one(&persister.pool)
+            .await
+            .unwrap();
+        assert_eq!(0, count.0);
+    }
+
+    #[tokio::test]
+    async fn persister_error_from_busy_snapshot() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("busy-snapshot.db");
+        let options = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true)
+            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(2)
+            .connect_with(options)
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE value (value INTEGER)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let mut reader = pool.begin().await.unwrap();
+        sqlx::query("SELECT COUNT(*) FROM value")
+            .execute(&mut *reader)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO value (value) VALUES (1)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // The write of a transaction reading an outdated snapshot fails with SQLITE_BUSY_SNAPSHOT (517)
+        let error = sqlx::query("INSERT INTO value (value) VALUES (2)")
+            .execute(&mut *reader)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            Some("517"),
+            error
+                .as_database_error()
+                .and_then(|error| error.code())
+                .as_deref()
+        );
+        assert!(matches!(
+            PersisterError::from(error),
+            PersisterError::Connection(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn persister_error_from_constraint_violation() {
+        let persister = setup_persister().await;
+        sqlx::query("CREATE TABLE unique_value (value TEXT UNIQUE)")
+            .execute(&persister.pool)
+            .await
+            .unwrap();
+        let insert = || {
+            sqlx::query("INSERT INTO unique_value (value) VALUES ('value')")
+                .execute(&persister.pool)
+        };
+        insert().await.unwrap();
+
+        let error = PersisterError::from(insert().await.unwrap_err());
+
+        assert!(matches!(error, PersisterError::Constraint(_)));
+    }
+}
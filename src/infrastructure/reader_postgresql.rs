@@ -0,0 +1,165 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use sqlx::{PgPool, Postgres, QueryBuilder, Row, postgres::PgPoolOptions, postgres::PgRow};
+
+use crate::{
+    Repository, RepositoryExport, RepositoryFilter, RepositoryMetadata, RepositoryOrder,
+    RepositoryReader, StdResult,
+};
+
+const SELECT_QUERY: &str = r#"
+SELECT node_id, repository_name, organization_name, owner_type, total_stars, database_id,
+    primary_language, fork_count, is_fork, is_archived, created_at, pushed_at, license,
+    description, topics
+FROM github.repository
+WHERE TRUE"#;
+
+/// A reader that retrieves repository metadata from a PostgreSQL database.
+pub struct PostgresSqlReader {
+    pool: PgPool,
+}
+
+impl PostgresSqlReader {
+    /// Creates a new `PostgresSqlReader` instance.
+    pub async fn try_new(connection_string: &str) -> StdResult<Self> {
+        Ok(Self {
+            pool: PgPoolOptions::new()
+                .max_connections(1)
+                .connect(connection_string)
+                .await?,
+        })
+    }
+
+    fn build_query(filter: &RepositoryFilter) -> QueryBuilder<'_, Postgres> {
+        let mut query_builder = QueryBuilder::new(SELECT_QUERY);
+        if let Some(organization_name) = &filter.organization_name {
+            query_builder
+                .push(" AND organization_name = ")
+                .push_bind(organization_name);
+        }
+        if let Some(min_stars) = filter.min_stars {
+            query_builder
+                .push(" AND total_stars >= ")
+                .push_bind(min_stars as i32);
+        }
+        match filter.order_by {
+            Some(RepositoryOrder::Stars) => {
+                query_builder.push(" ORDER BY total_stars DESC, id");
+            }
+            None => {
+                query_builder.push(" ORDER BY id");
+            }
+        }
+        if let Some(limit) = filter.limit {
+            query_builder.push(" LIMIT ").push_bind(limit as i64);
+        }
+
+        query_builder
+    }
+
+    fn parse_row(row: &PgRow) -> StdResult<Repository> {
+        let repository = Repository::new(
+            row.try_get::<&str, _>("repository_name")?,
+            row.try_get::<&str, _>("organization_name")?,
+            row.try_get::<i32, _>("total_stars")? as u32,
+        )
+        .with_metadata(RepositoryMetadata {
+            database_id: row.try_get("database_id")?,
+            primary_language: row.try_get("primary_language")?,
+            fork_count: row
+                .try_get::<Option<i32>, _>("fork_count")?
+                .map(|fork_count| fork_count as u32),
+            is_fork: row.try_get("is_fork")?,
+            is_archived: row.try_get("is_archived")?,
+            created_at: row.try_get::<Option<DateTime<Utc>>, _>("created_at")?,
+            pushed_at: row.try_get::<Option<DateTime<Utc>>, _>("pushed_at")?,
+            license: row.try_get("license")?,
+            description: row.try_get("description")?,
+            topics: row.try_get("topics")?,
+        });
+
+        let repository = match row.try_get::<Option<&str>, _>("owner_type")? {
+            Some(owner_type) => repository.with_owner_type(owner_type.parse()?),
+            None => repository,
+        };
+
+        Ok(match row.try_get::<Option<&str>, _>("node_id")? {
+            Some(node_id) => repository.with_node_id(node_id),
+            None => repository,
+        })
+    }
+}
+
+#[async_trait]
+impl RepositoryReader for PostgresSqlReader {
+    async fn read(&self, filter: &RepositoryFilter) -> StdResult<Vec<Repository>> {
+        Self::build_query(filter)
+            .build()
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(Self::parse_row)
+            .collect()
+    }
+
+    async fn read_in_batches<'a>(
+        &self,
+        filter: &RepositoryFilter,
+        batch_size: usize,
+        export: &mut (dyn RepositoryExport + 'a),
+    ) -> StdResult<u64> {
+        let batch_size = batch_size.max(1);
+        let mut query = Self::build_query(filter);
+        let mut rows = query.build().fetch(&self.pool);
+        let mut batch = Vec::with_capacity(batch_size);
+        let mut total_read = 0;
+        while let Some(row) = rows.try_next().await? {
+            batch.push(Self::parse_row(&row)?);
+            if batch.len() == batch_size {
+                export.write(&batch)?;
+                total_read += batch.len() as u64;
+                batch.clear();
+            }
+        }
+        if !batch.is_empty() {
+            export.write(&batch)?;
+            total_read += batch.len() as u64;
+        }
+
+        Ok(total_read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_query_without_filter() {
+        let filter = RepositoryFilter::default();
+
+        let query = PostgresSqlReader::build_query(&filter).into_sql();
+
+        assert!(query.ends_with("WHERE TRUE ORDER BY id"), "{query}");
+    }
+
+    #[test]
+    fn build_query_with_filter() {
+        let filter = RepositoryFilter {
+            organization_name: Some("org-1".to_string()),
+            min_stars: Some(100),
+            order_by: Some(RepositoryOrder::Stars),
+            limit: Some(10),
+        };
+
+        let query = PostgresSqlReader::build_query(&filter).into_sql();
+
+        assert!(
+            query.ends_with(
+                "WHERE TRUE AND organization_name = $1 AND total_stars >= $2 ORDER BY total_stars DESC, id LIMIT $3"
+            ),
+            "{query}"
+        );
+    }
+}
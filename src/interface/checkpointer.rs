@@ -0,0 +1,12 @@
+use crate::{CrawlerStateSnapshot, StdResult};
+
+/// A trait for saving and loading snapshots of the crawler state.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait CrawlerStateCheckpointer: Sync + Send {
+    /// Saves the snapshot of the crawler state for the given crawl.
+    async fn save(&self, crawl_id: &str, snapshot: &CrawlerStateSnapshot) -> StdResult<()>;
+
+    /// Loads the latest snapshot of the crawler state for the given crawl, if any.
+    async fn load(&self, crawl_id: &str) -> StdResult<Option<CrawlerStateSnapshot>>;
+}
@@ -6,4 +6,9 @@ use crate::{Request, StdResult};
 pub trait RepositoryCrawler: Sync + Send {
     /// Crawl the GitHub API for repositories.
     async fn crawl(&self, requests: Vec<Request>, total_repositories: u32) -> StdResult<()>;
+
+    /// Flushes the repositories buffered by the crawler, so that the requests it acknowledged are persisted.
+    async fn flush(&self) -> StdResult<()> {
+        Ok(())
+    }
 }
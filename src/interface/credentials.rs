@@ -0,0 +1,9 @@
+use crate::StdResult;
+
+/// A trait for providing the credentials used to authenticate to the GitHub API.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait CredentialProvider: Sync + Send {
+    /// Provides a valid API token.
+    async fn token(&self) -> StdResult<String>;
+}
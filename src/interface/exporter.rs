@@ -0,0 +1,34 @@
+use std::io::Write;
+
+use crate::{ExportColumn, Repository, StdResult};
+
+/// A trait for exporting repository data to a file format.
+pub trait RepositoryExporter: Sync + Send {
+    /// Starts an export of the given columns to the writer, the repositories being then written in batches.
+    fn start<'a>(
+        &self,
+        columns: &'a [ExportColumn],
+        writer: &'a mut (dyn Write + Send),
+    ) -> StdResult<Box<dyn RepositoryExport + 'a>>;
+
+    /// Exports the given columns of the repositories to the writer.
+    fn export(
+        &self,
+        repositories: &[Repository],
+        columns: &[ExportColumn],
+        writer: &mut (dyn Write + Send),
+    ) -> StdResult<()> {
+        let mut export = self.start(columns, writer)?;
+        export.write(repositories)?;
+        export.finish()
+    }
+}
+
+/// An export in progress, to which the repositories are written in batches.
+pub trait RepositoryExport: Send {
+    /// Writes a batch of repositories to the export.
+    fn write(&mut self, repositories: &[Repository]) -> StdResult<()>;
+
+    /// Completes the export, flushing the writer.
+    fn finish(self: Box<Self>) -> StdResult<()>;
+}
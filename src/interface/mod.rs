@@ -1,7 +1,15 @@
+mod checkpointer;
 mod crawler;
+mod credentials;
+mod exporter;
 mod fetcher;
 mod persister;
+mod reader;
 
+pub use checkpointer::*;
 pub use crawler::*;
+pub use credentials::*;
+pub use exporter::*;
 pub use fetcher::*;
 pub use persister::*;
+pub use reader::*;
@@ -1,9 +1,28 @@
-use crate::{Repository, StdResult};
+use anyhow::anyhow;
+
+use crate::{PersistReport, Repository, StdResult};
 
 /// A trait for persisting repository data to a storage medium.
 #[cfg_attr(test, mockall::automock)]
 #[async_trait::async_trait]
 pub trait RepositoryPersister: Sync + Send {
     /// Persists the repository data to a storage medium.
-    async fn persist(&self, data: &[Repository]) -> StdResult<u32>;
+    async fn persist(&self, data: &[Repository]) -> StdResult<PersistReport>;
+
+    /// Flushes the repository data buffered by the persister, if any.
+    async fn flush(&self) -> StdResult<()> {
+        Ok(())
+    }
+
+    /// Marks the repositories with the given node identifiers as deleted or inaccessible, returning how many were marked.
+    async fn mark_unreachable(&self, _node_ids: &[String]) -> StdResult<u32> {
+        Ok(0)
+    }
+
+    /// Reads up to `limit` persisted repositories not marked unreachable with their node identifier and stars, in a stable order.
+    async fn read_known_repositories(&self, _limit: u32) -> StdResult<Vec<Repository>> {
+        Err(anyhow!(
+            "Reading the known repositories is not supported by this persister"
+        ))
+    }
 }
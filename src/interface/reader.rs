@@ -0,0 +1,21 @@
+use crate::{Repository, RepositoryExport, RepositoryFilter, StdResult};
+
+/// A trait for reading repository data from a storage medium.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait RepositoryReader: Sync + Send {
+    /// Reads the repositories matching the filter from a storage medium.
+    async fn read(&self, filter: &RepositoryFilter) -> StdResult<Vec<Repository>>;
+
+    /// Streams the repositories matching the filter from a storage medium to the export, in batches of up to `batch_size`.
+    ///
+    /// Returns the total number of repositories read.
+    // The parentheses are required around the trait object, but the expanded mock reports them as unused
+    #[allow(unused_parens)]
+    async fn read_in_batches<'a>(
+        &self,
+        filter: &RepositoryFilter,
+        batch_size: usize,
+        export: &mut (dyn RepositoryExport + 'a),
+    ) -> StdResult<u64>;
+}
@@ -1,19 +1,20 @@
 use std::{
-    collections::{BinaryHeap, HashSet},
+    collections::{BTreeMap, BinaryHeap, HashMap, HashSet},
     fmt::Display,
     ops::Deref,
+    str::FromStr,
     time::Duration,
 };
 
 use chrono::{DateTime, Utc};
 use log::info;
-use serde::Deserialize;
-use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Notify, RwLock};
 
-use super::{Request, StdResult};
+use super::{LatencyHistogram, Request, StdResult};
 
 /// The name of a repository.
-#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct RepositoryName(pub String);
 
 impl Deref for RepositoryName {
@@ -31,7 +32,7 @@ impl Display for RepositoryName {
 }
 
 /// The name of an organization.
-#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct OrganizationName(pub String);
 
 impl Deref for OrganizationName {
@@ -48,8 +49,26 @@ impl Display for OrganizationName {
     }
 }
 
+/// The GraphQL node identifier of a repository, stable across renames and transfers.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryNodeId(pub String);
+
+impl Deref for RepositoryNodeId {
+    type Target = String;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Display for RepositoryNodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// The number of stars a repository has.
-#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct StarsCounter(pub u32);
 
 impl Deref for StarsCounter {
@@ -65,29 +84,182 @@ impl Display for StarsCounter {
         write!(f, "{}", self.0)
     }
 }
+
+/// The type of the GitHub account that owns a repository.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum OwnerType {
+    /// An organization account.
+    Organization,
+
+    /// A personal user account.
+    User,
+}
+
+impl OwnerType {
+    /// Retrieves the name of the owner type, as stored in the databases.
+    pub fn name(&self) -> &'static str {
+        match self {
+            OwnerType::Organization => "organization",
+            OwnerType::User => "user",
+        }
+    }
+}
+
+impl FromStr for OwnerType {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> StdResult<Self> {
+        match name {
+            "organization" => Ok(OwnerType::Organization),
+            "user" => Ok(OwnerType::User),
+            _ => Err(anyhow::anyhow!("Unknown owner type: {name}")),
+        }
+    }
+}
+
+impl Display for OwnerType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// An optional field of the metadata of a repository that can be fetched.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RepositoryField {
+    /// The database identifier of the repository.
+    DatabaseId,
+
+    /// The primary language of the repository.
+    PrimaryLanguage,
+
+    /// The number of forks of the repository.
+    ForkCount,
+
+    /// Whether the repository is a fork.
+    IsFork,
+
+    /// Whether the repository is archived.
+    IsArchived,
+
+    /// The creation time of the repository.
+    CreatedAt,
+
+    /// The time of the latest push to the repository.
+    PushedAt,
+
+    /// The SPDX identifier of the license of the repository.
+    License,
+
+    /// The description of the repository.
+    Description,
+
+    /// The topics of the repository.
+    Topics,
+}
+
+/// Optional metadata of a GitHub repository, only available if the matching fields have been fetched.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepositoryMetadata {
+    /// The database identifier of the repository.
+    pub database_id: Option<i64>,
+
+    /// The primary language of the repository.
+    pub primary_language: Option<String>,
+
+    /// The number of forks of the repository.
+    pub fork_count: Option<u32>,
+
+    /// Whether the repository is a fork.
+    pub is_fork: Option<bool>,
+
+    /// Whether the repository is archived.
+    pub is_archived: Option<bool>,
+
+    /// The creation time of the repository.
+    pub created_at: Option<DateTime<Utc>>,
+
+    /// The time of the latest push to the repository.
+    pub pushed_at: Option<DateTime<Utc>>,
+
+    /// The SPDX identifier of the license of the repository.
+    pub license: Option<String>,
+
+    /// The description of the repository.
+    pub description: Option<String>,
+
+    /// The topics of the repository.
+    pub topics: Option<Vec<String>>,
+}
+
 /// Metadata of a GitHub repository.
-#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Repository {
+    /// The GraphQL node identifier of the repository.
+    node_id: Option<RepositoryNodeId>,
+
     /// The name of the repository.
     repository_name: RepositoryName,
 
-    /// The name of the organization that owns the repository.
+    /// The name of the organization, or of the user, that owns the repository.
     organization_name: OrganizationName,
 
+    /// The type of the account that owns the repository.
+    owner_type: Option<OwnerType>,
+
     /// The number of stars the repository has.
     total_stars: StarsCounter,
+
+    /// The time at which the repository metadata was observed.
+    observed_at: Option<DateTime<Utc>>,
+
+    /// The optional metadata of the repository.
+    metadata: RepositoryMetadata,
 }
 
 impl Repository {
     /// Creates a new `Repository` instance.
     pub fn new(repository_name: &str, organization_name: &str, total_stars: u32) -> Self {
         Self {
+            node_id: None,
             repository_name: RepositoryName(repository_name.to_string()),
             organization_name: OrganizationName(organization_name.to_string()),
+            owner_type: None,
             total_stars: StarsCounter(total_stars),
+            observed_at: None,
+            metadata: RepositoryMetadata::default(),
         }
     }
 
+    /// Sets the optional metadata of the repository.
+    pub fn with_metadata(mut self, metadata: RepositoryMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Sets the GraphQL node identifier of the repository.
+    pub fn with_node_id(mut self, node_id: &str) -> Self {
+        self.node_id = Some(RepositoryNodeId(node_id.to_string()));
+        self
+    }
+
+    /// Sets the type of the account that owns the repository.
+    pub fn with_owner_type(mut self, owner_type: OwnerType) -> Self {
+        self.owner_type = Some(owner_type);
+        self
+    }
+
+    /// Sets the time at which the repository metadata was observed.
+    pub fn with_observed_at(mut self, observed_at: DateTime<Utc>) -> Self {
+        self.observed_at = Some(observed_at);
+        self
+    }
+
+    /// Retrieves the GraphQL node identifier of the repository.
+    pub fn node_id(&self) -> Option<&RepositoryNodeId> {
+        self.node_id.as_ref()
+    }
+
     /// Retrieves the repository name.
     pub fn repository_name(&self) -> &RepositoryName {
         &self.repository_name
@@ -98,10 +270,25 @@ impl Repository {
         &self.organization_name
     }
 
+    /// Retrieves the type of the account that owns the repository.
+    pub fn owner_type(&self) -> Option<OwnerType> {
+        self.owner_type
+    }
+
     /// Retrieves the total stars of the repository.
     pub fn total_stars(&self) -> &StarsCounter {
         &self.total_stars
     }
+
+    /// Retrieves the time at which the repository metadata was observed.
+    pub fn observed_at(&self) -> Option<DateTime<Utc>> {
+        self.observed_at
+    }
+
+    /// Retrieves the optional metadata of the repository.
+    pub fn metadata(&self) -> &RepositoryMetadata {
+        &self.metadata
+    }
 }
 
 impl Display for Repository {
@@ -114,6 +301,26 @@ impl Display for Repository {
     }
 }
 
+/// The outcome of persisting repositories.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PersistReport {
+    /// The number of repositories that were inserted.
+    pub total_inserted: u32,
+
+    /// The number of repositories that were not inserted because they already exist.
+    pub total_collisions: u32,
+}
+
+impl PersistReport {
+    /// Creates a new `PersistReport` instance from the number of repositories persisted and inserted.
+    pub fn new(total_repositories: u32, total_inserted: u32) -> Self {
+        Self {
+            total_inserted,
+            total_collisions: total_repositories.saturating_sub(total_inserted),
+        }
+    }
+}
+
 /// A state for the sequential crawler
 #[derive(Debug, Default)]
 pub struct CrawlerState {
@@ -138,14 +345,140 @@ pub struct CrawlerState {
     /// The total number of collisions (repositories that were not persisted because they already exist)
     total_collisions_repositories: RwLock<u32>,
 
-    /// The API rate limit for the fetchers
-    current_api_rate_limit: RwLock<FetcherRateLimit>,
+    /// The total number of known repositories refreshed
+    total_refreshed_repositories: RwLock<u32>,
+
+    /// The total number of known repositories marked as deleted or inaccessible
+    total_unreachable_repositories: RwLock<u32>,
+
+    /// The total number of repositories buffered but not yet persisted, not kept in the snapshots as the buffers of a run are flushed or lost with it
+    total_pending_repositories: RwLock<u32>,
+
+    /// The API rate limits for the fetchers, by API token label, not kept in the snapshots as they are observed again by each run
+    api_rate_limits: RwLock<BTreeMap<String, FetcherRateLimit>>,
+
+    /// The total number of fetch attempts that were retried
+    total_fetcher_retries: RwLock<u32>,
+
+    /// The total number of persist attempts that were retried
+    total_persister_retries: RwLock<u32>,
+
+    /// The latencies of the requests, by request kind
+    request_latencies: RwLock<BTreeMap<String, LatencyHistogram>>,
+
+    /// The total number of API rate limit points spent by the fetchers
+    total_rate_limit_points_spent: RwLock<u32>,
+
+    /// The total number of responses and search results skipped because they could not be parsed
+    total_parse_errors: RwLock<u32>,
+
+    /// The total number of node-level errors of partial GraphQL responses
+    total_node_errors: RwLock<u32>,
+
+    /// The total number of requests skipped after a permanent error
+    total_failed_requests: RwLock<u32>,
+
+    /// The seed query through which each organization was found, by organization name
+    organization_seed_queries: RwLock<HashMap<String, String>>,
+
+    /// The number of repositories fetched through each seed query
+    seed_query_yields: RwLock<BTreeMap<String, u32>>,
+
+    /// The page size adapted for the requests, by request kind
+    page_sizes: RwLock<BTreeMap<String, u16>>,
+
+    /// The total number of adjustments of the page size of the requests
+    total_page_size_adjustments: RwLock<u32>,
+
+    /// Whether a shutdown of the crawler has been requested
+    shutdown_requested: RwLock<bool>,
+
+    /// A notification sent when a shutdown of the crawler is requested
+    shutdown_notify: Notify,
 }
 
 impl CrawlerState {
+    /// Creates a new `CrawlerState` instance restored from a snapshot.
+    ///
+    /// The requests that were in progress when the snapshot was taken are queued again, and the totals carry on
+    /// from the snapshot, except the pending repositories and the API rate limits that are specific to a run.
+    pub fn from_snapshot(snapshot: CrawlerStateSnapshot) -> Self {
+        let mut requests_priority_queue = BinaryHeap::from(snapshot.requests_queued);
+        requests_priority_queue.extend(snapshot.requests_in_progress);
+
+        Self {
+            requests_priority_queue: RwLock::new(requests_priority_queue),
+            requests_pushed: RwLock::new(snapshot.requests_pushed.into_iter().collect()),
+            requests_in_progress: RwLock::new(HashSet::new()),
+            total_repositories_target: RwLock::new(snapshot.total_repositories_target),
+            total_fetcher_calls: RwLock::new(snapshot.total_fetcher_calls),
+            total_persisted_repositories: RwLock::new(snapshot.total_persisted_repositories),
+            total_collisions_repositories: RwLock::new(snapshot.total_collisions_repositories),
+            total_refreshed_repositories: RwLock::new(snapshot.total_refreshed_repositories),
+            total_unreachable_repositories: RwLock::new(snapshot.total_unreachable_repositories),
+            total_pending_repositories: RwLock::new(0),
+            api_rate_limits: RwLock::new(BTreeMap::new()),
+            total_fetcher_retries: RwLock::new(snapshot.total_fetcher_retries),
+            total_persister_retries: RwLock::new(snapshot.total_persister_retries),
+            request_latencies: RwLock::new(snapshot.request_latencies),
+            total_rate_limit_points_spent: RwLock::new(snapshot.total_rate_limit_points_spent),
+            total_parse_errors: RwLock::new(snapshot.total_parse_errors),
+            total_node_errors: RwLock::new(snapshot.total_node_errors),
+            total_failed_requests: RwLock::new(snapshot.total_failed_requests),
+            organization_seed_queries: RwLock::new(
+                snapshot.organization_seed_queries.into_iter().collect(),
+            ),
+            seed_query_yields: RwLock::new(snapshot.seed_query_yields),
+            page_sizes: RwLock::new(snapshot.page_sizes),
+            total_page_size_adjustments: RwLock::new(snapshot.total_page_size_adjustments),
+            shutdown_requested: RwLock::new(false),
+            shutdown_notify: Notify::new(),
+        }
+    }
+
+    /// Takes a snapshot of the state.
+    pub async fn snapshot(&self) -> CrawlerStateSnapshot {
+        let requests_priority_queue = self.requests_priority_queue.read().await;
+        let requests_in_progress = self.requests_in_progress.read().await;
+        let requests_pushed = self.requests_pushed.read().await;
+
+        CrawlerStateSnapshot {
+            requests_queued: requests_priority_queue.iter().cloned().collect(),
+            requests_pushed: requests_pushed.iter().cloned().collect(),
+            requests_in_progress: requests_in_progress.iter().cloned().collect(),
+            total_repositories_target: self.get_total_repositories_target().await,
+            total_fetcher_calls: self.get_total_fetcher_calls().await,
+            total_persisted_repositories: self.get_total_persisted_repositories().await,
+            total_collisions_repositories: self.get_total_collisions_repositories().await,
+            total_refreshed_repositories: self.get_total_refreshed_repositories().await,
+            total_unreachable_repositories: self.get_total_unreachable_repositories().await,
+            total_fetcher_retries: self.get_total_fetcher_retries().await,
+            total_persister_retries: self.get_total_persister_retries().await,
+            request_latencies: self.get_request_latencies().await,
+            total_rate_limit_points_spent: self.get_total_rate_limit_points_spent().await,
+            total_parse_errors: self.get_total_parse_errors().await,
+            total_node_errors: self.get_total_node_errors().await,
+            total_failed_requests: self.get_total_failed_requests().await,
+            organization_seed_queries: self
+                .organization_seed_queries
+                .read()
+                .await
+                .iter()
+                .map(|(organization_name, seed_query)| {
+                    (organization_name.to_owned(), seed_query.to_owned())
+                })
+                .collect(),
+            seed_query_yields: self.get_seed_query_yields().await,
+            page_sizes: self.get_page_sizes().await,
+            total_page_size_adjustments: self.get_total_page_size_adjustments().await,
+        }
+    }
+
     pub async fn has_completed(&self) -> StdResult<bool> {
         let total_repositories_target = self.get_total_repositories_target().await;
-        let total_persisted_repositories = self.get_total_persisted_repositories().await;
+        // The refreshed repositories count towards the target of a refresh crawl
+        let total_persisted_repositories = self.get_total_persisted_repositories().await
+            + self.get_total_refreshed_repositories().await;
         let has_persisted_enough_repositories =
             total_persisted_repositories >= total_repositories_target;
 
@@ -163,7 +496,7 @@ impl CrawlerState {
             let has_in_progress_requests = {
                 let requests_in_progress = self.requests_in_progress.read().await;
                 !(*requests_in_progress).is_empty()
-            };
+            } || self.get_total_pending_repositories().await > 0;
             let has_failed = has_empty_priority_queue
                 && has_pushed_requests
                 && !has_in_progress_requests
@@ -199,10 +532,12 @@ impl CrawlerState {
         }
     }
 
-    /// Acknowledges a request that has been processed.
+    /// Acknowledges a request that has been processed, or each request of a batch.
     pub async fn acknowledge_request(&self, request: &Request) {
         let mut requests_in_progress = self.requests_in_progress.write().await;
-        (*requests_in_progress).remove(request);
+        for request in request.unbatch() {
+            (*requests_in_progress).remove(&request);
+        }
     }
 
     /// Pops a request from the priority queue.
@@ -217,6 +552,86 @@ impl CrawlerState {
         popped_request
     }
 
+    /// Pops a request from the priority queue, coalescing it with the next organization repositories requests into a batch of at most `max_batch_size` requests.
+    pub async fn pop_request_batch(&self, max_batch_size: usize) -> Option<Request> {
+        let mut requests_priority_queue = self.requests_priority_queue.write().await;
+        let popped_request = (*requests_priority_queue).pop()?;
+        let mut batch = match popped_request {
+            Request::OrganizationRepositories(request) => vec![request],
+            request => {
+                let mut requests_in_progress = self.requests_in_progress.write().await;
+                (*requests_in_progress).insert(request.to_owned());
+                return Some(request);
+            }
+        };
+        while batch.len() < max_batch_size
+            && matches!(
+                (*requests_priority_queue).peek(),
+                Some(Request::OrganizationRepositories(_))
+            )
+        {
+            if let Some(Request::OrganizationRepositories(request)) =
+                (*requests_priority_queue).pop()
+            {
+                batch.push(request);
+            }
+        }
+        let request = match batch.len() {
+            1 => Request::OrganizationRepositories(batch.remove(0)),
+            _ => Request::OrganizationRepositoriesBatch(batch),
+        };
+        let mut requests_in_progress = self.requests_in_progress.write().await;
+        (*requests_in_progress).extend(request.unbatch());
+
+        Some(request)
+    }
+
+    /// Retrieves the number of requests waiting in the priority queue.
+    pub async fn get_total_queued_requests(&self) -> usize {
+        let requests_priority_queue = self.requests_priority_queue.read().await;
+        requests_priority_queue.len()
+    }
+
+    /// Retrieves the number of requests in progress.
+    pub async fn get_total_in_progress_requests(&self) -> usize {
+        let requests_in_progress = self.requests_in_progress.read().await;
+        requests_in_progress.len()
+    }
+
+    /// Moves the requests in progress back to the priority queue.
+    pub async fn requeue_in_progress_requests(&self) -> usize {
+        let mut requests_priority_queue = self.requests_priority_queue.write().await;
+        let mut requests_in_progress = self.requests_in_progress.write().await;
+        let total_requeued_requests = requests_in_progress.len();
+        requests_priority_queue.extend(requests_in_progress.drain());
+
+        total_requeued_requests
+    }
+
+    /// Requests a shutdown of the crawler: no more requests should be popped from the queue.
+    pub async fn request_shutdown(&self) {
+        let mut shutdown_requested = self.shutdown_requested.write().await;
+        *shutdown_requested = true;
+        self.shutdown_notify.notify_waiters();
+    }
+
+    /// Checks whether a shutdown of the crawler has been requested.
+    pub async fn is_shutdown_requested(&self) -> bool {
+        let shutdown_requested = self.shutdown_requested.read().await;
+        *shutdown_requested
+    }
+
+    /// Waits until a shutdown of the crawler is requested.
+    pub async fn wait_for_shutdown(&self) {
+        loop {
+            let notified = self.shutdown_notify.notified();
+            if self.is_shutdown_requested().await {
+                return;
+            }
+            notified.await;
+        }
+    }
+
     /// Sets the total number of repositories to be fetched.
     pub async fn set_total_repositories_target(&self, total_repositories: u32) {
         let mut total_repositories_target = self.total_repositories_target.write().await;
@@ -253,6 +668,48 @@ impl CrawlerState {
         *total_collisions_repositories
     }
 
+    /// Increments the total number of known repositories refreshed.
+    pub async fn increment_total_refreshed_repositories(&self, increment: u32) {
+        let mut total_refreshed_repositories = self.total_refreshed_repositories.write().await;
+        *total_refreshed_repositories += increment;
+    }
+
+    /// Retrieves the total number of known repositories refreshed.
+    pub async fn get_total_refreshed_repositories(&self) -> u32 {
+        let total_refreshed_repositories = self.total_refreshed_repositories.read().await;
+        *total_refreshed_repositories
+    }
+
+    /// Increments the total number of known repositories marked as deleted or inaccessible.
+    pub async fn increment_total_unreachable_repositories(&self, increment: u32) {
+        let mut total_unreachable_repositories = self.total_unreachable_repositories.write().await;
+        *total_unreachable_repositories += increment;
+    }
+
+    /// Retrieves the total number of known repositories marked as deleted or inaccessible.
+    pub async fn get_total_unreachable_repositories(&self) -> u32 {
+        let total_unreachable_repositories = self.total_unreachable_repositories.read().await;
+        *total_unreachable_repositories
+    }
+
+    /// Increments the total number of repositories buffered but not yet persisted.
+    pub async fn increment_total_pending_repositories(&self, increment: u32) {
+        let mut total_pending_repositories = self.total_pending_repositories.write().await;
+        *total_pending_repositories += increment;
+    }
+
+    /// Decrements the total number of repositories buffered but not yet persisted.
+    pub async fn decrement_total_pending_repositories(&self, decrement: u32) {
+        let mut total_pending_repositories = self.total_pending_repositories.write().await;
+        *total_pending_repositories = total_pending_repositories.saturating_sub(decrement);
+    }
+
+    /// Retrieves the total number of repositories buffered but not yet persisted.
+    pub async fn get_total_pending_repositories(&self) -> u32 {
+        let total_pending_repositories = self.total_pending_repositories.read().await;
+        *total_pending_repositories
+    }
+
     /// Increments the total number of fetcher calls.
     pub async fn increment_total_fetcher_calls(&self, increment: u32) {
         let mut total_fetcher_calls = self.total_fetcher_calls.write().await;
@@ -265,16 +722,155 @@ impl CrawlerState {
         *total_fetcher_calls
     }
 
-    /// Updates the API rate limit.
-    pub async fn update_current_api_rate_limit(&self, rate_limit: FetcherRateLimit) {
-        let mut api_rate_limit = self.current_api_rate_limit.write().await;
-        *api_rate_limit = rate_limit;
+    /// Increments the total number of retried fetch attempts.
+    pub async fn increment_total_fetcher_retries(&self, increment: u32) {
+        let mut total_fetcher_retries = self.total_fetcher_retries.write().await;
+        *total_fetcher_retries += increment;
+    }
+
+    /// Retrieves the total number of retried fetch attempts.
+    pub async fn get_total_fetcher_retries(&self) -> u32 {
+        let total_fetcher_retries = self.total_fetcher_retries.read().await;
+        *total_fetcher_retries
+    }
+
+    /// Increments the total number of retried persist attempts.
+    pub async fn increment_total_persister_retries(&self, increment: u32) {
+        let mut total_persister_retries = self.total_persister_retries.write().await;
+        *total_persister_retries += increment;
+    }
+
+    /// Retrieves the total number of retried persist attempts.
+    pub async fn get_total_persister_retries(&self) -> u32 {
+        let total_persister_retries = self.total_persister_retries.read().await;
+        *total_persister_retries
+    }
+
+    /// Records the latency of a request of the given kind.
+    pub async fn observe_request_latency(&self, request_kind: &str, latency: Duration) {
+        let mut request_latencies = self.request_latencies.write().await;
+        request_latencies
+            .entry(request_kind.to_string())
+            .or_default()
+            .observe(latency);
+    }
+
+    /// Retrieves the latencies of the requests by request kind.
+    pub async fn get_request_latencies(&self) -> BTreeMap<String, LatencyHistogram> {
+        let request_latencies = self.request_latencies.read().await;
+        request_latencies.to_owned()
     }
 
-    /// Retrieves the current API rate limit.
-    pub async fn get_current_api_rate_limit(&self) -> FetcherRateLimit {
-        let api_rate_limit = self.current_api_rate_limit.read().await;
-        api_rate_limit.to_owned()
+    /// Increments the total number of API rate limit points spent.
+    pub async fn increment_total_rate_limit_points_spent(&self, increment: u32) {
+        let mut total_rate_limit_points_spent = self.total_rate_limit_points_spent.write().await;
+        *total_rate_limit_points_spent += increment;
+    }
+
+    /// Retrieves the total number of API rate limit points spent.
+    pub async fn get_total_rate_limit_points_spent(&self) -> u32 {
+        let total_rate_limit_points_spent = self.total_rate_limit_points_spent.read().await;
+        *total_rate_limit_points_spent
+    }
+
+    /// Increments the total number of responses and search results skipped because they could not be parsed.
+    pub async fn increment_total_parse_errors(&self, increment: u32) {
+        let mut total_parse_errors = self.total_parse_errors.write().await;
+        *total_parse_errors += increment;
+    }
+
+    /// Retrieves the total number of responses and search results skipped because they could not be parsed.
+    pub async fn get_total_parse_errors(&self) -> u32 {
+        let total_parse_errors = self.total_parse_errors.read().await;
+        *total_parse_errors
+    }
+
+    /// Increments the total number of node-level errors of partial GraphQL responses.
+    pub async fn increment_total_node_errors(&self, increment: u32) {
+        let mut total_node_errors = self.total_node_errors.write().await;
+        *total_node_errors += increment;
+    }
+
+    /// Retrieves the total number of node-level errors of partial GraphQL responses.
+    pub async fn get_total_node_errors(&self) -> u32 {
+        let total_node_errors = self.total_node_errors.read().await;
+        *total_node_errors
+    }
+
+    /// Increments the total number of requests skipped after a permanent error.
+    pub async fn increment_total_failed_requests(&self, increment: u32) {
+        let mut total_failed_requests = self.total_failed_requests.write().await;
+        *total_failed_requests += increment;
+    }
+
+    /// Retrieves the total number of requests skipped after a permanent error.
+    pub async fn get_total_failed_requests(&self) -> u32 {
+        let total_failed_requests = self.total_failed_requests.read().await;
+        *total_failed_requests
+    }
+
+    /// Records the seed query through which the organizations were found, keeping the first one.
+    pub async fn assign_seed_query(&self, seed_query: &str, organization_names: &[&str]) {
+        self.seed_query_yields
+            .write()
+            .await
+            .entry(seed_query.to_string())
+            .or_insert(0);
+        let mut organization_seed_queries = self.organization_seed_queries.write().await;
+        for organization_name in organization_names {
+            organization_seed_queries
+                .entry(organization_name.to_string())
+                .or_insert_with(|| seed_query.to_string());
+        }
+    }
+
+    /// Increments the number of repositories fetched through the seed query of the organization.
+    pub async fn increment_seed_query_yield(&self, organization_name: &str, increment: u32) {
+        let organization_seed_queries = self.organization_seed_queries.read().await;
+        if let Some(seed_query) = organization_seed_queries.get(organization_name) {
+            let mut seed_query_yields = self.seed_query_yields.write().await;
+            *seed_query_yields.entry(seed_query.to_owned()).or_insert(0) += increment;
+        }
+    }
+
+    /// Retrieves the number of repositories fetched through each seed query.
+    pub async fn get_seed_query_yields(&self) -> BTreeMap<String, u32> {
+        let seed_query_yields = self.seed_query_yields.read().await;
+        seed_query_yields.to_owned()
+    }
+
+    /// Records an adjustment of the page size of the requests of the given kind.
+    pub async fn record_page_size_adjustment(&self, request_kind: &str, first: u16) {
+        self.page_sizes
+            .write()
+            .await
+            .insert(request_kind.to_string(), first);
+        let mut total_page_size_adjustments = self.total_page_size_adjustments.write().await;
+        *total_page_size_adjustments += 1;
+    }
+
+    /// Retrieves the page size adapted for the requests, by request kind.
+    pub async fn get_page_sizes(&self) -> BTreeMap<String, u16> {
+        let page_sizes = self.page_sizes.read().await;
+        page_sizes.to_owned()
+    }
+
+    /// Retrieves the total number of adjustments of the page size of the requests.
+    pub async fn get_total_page_size_adjustments(&self) -> u32 {
+        let total_page_size_adjustments = self.total_page_size_adjustments.read().await;
+        *total_page_size_adjustments
+    }
+
+    /// Updates the API rate limit of the given API token.
+    pub async fn update_api_rate_limit(&self, api_token: &str, rate_limit: FetcherRateLimit) {
+        let mut api_rate_limits = self.api_rate_limits.write().await;
+        api_rate_limits.insert(api_token.to_string(), rate_limit);
+    }
+
+    /// Retrieves the API rate limits by API token.
+    pub async fn get_api_rate_limits(&self) -> BTreeMap<String, FetcherRateLimit> {
+        let api_rate_limits = self.api_rate_limits.read().await;
+        api_rate_limits.to_owned()
     }
 
     /// Returns the summary of the state.
@@ -282,18 +878,106 @@ impl CrawlerState {
         let total_fetcher_calls = self.total_fetcher_calls.read().await;
         let total_persisted_repositories = self.total_persisted_repositories.read().await;
         let total_collisions_repositories = self.total_collisions_repositories.read().await;
-        let current_api_rate_limit = self.current_api_rate_limit.read().await;
+        let total_pending_repositories = self.total_pending_repositories.read().await;
+        let api_rate_limits = self
+            .api_rate_limits
+            .read()
+            .await
+            .iter()
+            .map(|(api_token, rate_limit)| format!("{api_token}: {rate_limit}"))
+            .collect::<Vec<_>>()
+            .join(", ");
         let total_buffered_requests = self.requests_priority_queue.read().await.len();
         let total_in_progress_requests = self.requests_in_progress.read().await.len();
         let total_repositories_target = self.get_total_repositories_target().await;
 
         format!(
-            "Repositories: done={total_persisted_repositories}/{total_repositories_target}, collisions={total_collisions_repositories}, Requests: done={total_fetcher_calls} in_progress={total_in_progress_requests} buffered={total_buffered_requests}, {current_api_rate_limit}",
+            "Repositories: done={total_persisted_repositories}/{total_repositories_target}, collisions={total_collisions_repositories} pending={total_pending_repositories}, Requests: done={total_fetcher_calls} in_progress={total_in_progress_requests} buffered={total_buffered_requests}, {api_rate_limits}",
         )
     }
 }
 
-const FETCHER_RATE_LIMIT_MIN_REMAINING_ALLOWED: i32 = 10;
+/// A serializable snapshot of the state of the crawler, used to resume a crawl
+///
+/// The totals missing from the checkpoints of earlier versions are restored as zero.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct CrawlerStateSnapshot {
+    /// The requests waiting in the priority queue
+    pub requests_queued: Vec<Request>,
+
+    /// The requests that have already been pushed to the queue
+    pub requests_pushed: Vec<Request>,
+
+    /// The requests that were in progress
+    pub requests_in_progress: Vec<Request>,
+
+    /// The total number of repositories to be fetched
+    pub total_repositories_target: u32,
+
+    /// The total number of fetcher calls made
+    pub total_fetcher_calls: u32,
+
+    /// The total number of repositories persisted
+    pub total_persisted_repositories: u32,
+
+    /// The total number of collisions
+    pub total_collisions_repositories: u32,
+
+    /// The total number of known repositories refreshed
+    #[serde(default)]
+    pub total_refreshed_repositories: u32,
+
+    /// The total number of known repositories marked as deleted or inaccessible
+    #[serde(default)]
+    pub total_unreachable_repositories: u32,
+
+    /// The total number of fetch attempts that were retried
+    #[serde(default)]
+    pub total_fetcher_retries: u32,
+
+    /// The total number of persist attempts that were retried
+    #[serde(default)]
+    pub total_persister_retries: u32,
+
+    /// The latencies of the requests, by request kind
+    #[serde(default)]
+    pub request_latencies: BTreeMap<String, LatencyHistogram>,
+
+    /// The total number of API rate limit points spent by the fetchers
+    #[serde(default)]
+    pub total_rate_limit_points_spent: u32,
+
+    /// The total number of responses and search results skipped because they could not be parsed
+    #[serde(default)]
+    pub total_parse_errors: u32,
+
+    /// The total number of node-level errors of partial GraphQL responses
+    #[serde(default)]
+    pub total_node_errors: u32,
+
+    /// The total number of requests skipped after a permanent error
+    #[serde(default)]
+    pub total_failed_requests: u32,
+
+    /// The seed query through which each organization was found, by organization name
+    #[serde(default)]
+    pub organization_seed_queries: BTreeMap<String, String>,
+
+    /// The number of repositories fetched through each seed query
+    #[serde(default)]
+    pub seed_query_yields: BTreeMap<String, u32>,
+
+    /// The page size adapted for the requests, by request kind
+    #[serde(default)]
+    pub page_sizes: BTreeMap<String, u16>,
+
+    /// The total number of adjustments of the page size of the requests
+    #[serde(default)]
+    pub total_page_size_adjustments: u32,
+}
+
+/// The number of remaining rate limit points under which a rate limit is considered exceeded.
+pub(crate) const FETCHER_RATE_LIMIT_MIN_REMAINING_ALLOWED: i32 = 10;
 
 /// A fetcher API rate limit
 #[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
@@ -396,6 +1080,21 @@ mod tests {
             assert!(!result);
         }
 
+        #[tokio::test]
+        async fn has_not_completed_when_queue_empty_and_repositories_pending() {
+            let state = CrawlerState::default();
+            state.set_total_repositories_target(10).await;
+            let request = Request::dummy_search_organization();
+            state.push_request(request.clone()).await;
+            state.pop_request().await;
+            state.acknowledge_request(&request).await;
+            state.increment_total_pending_repositories(10).await;
+
+            let result = state.has_completed().await.unwrap();
+
+            assert!(!result);
+        }
+
         #[tokio::test]
         async fn has_not_completed_when_no_requests_pushed() {
             let state = CrawlerState::default();
@@ -476,6 +1175,67 @@ mod tests {
             assert_eq!(total_collisions, 5);
         }
 
+        #[tokio::test]
+        async fn increment_decrement_and_get_total_pending_repositories() {
+            let state = CrawlerState::default();
+
+            state.increment_total_pending_repositories(5).await;
+            state.decrement_total_pending_repositories(2).await;
+            let total_pending = state.get_total_pending_repositories().await;
+
+            assert_eq!(total_pending, 3);
+        }
+
+        #[tokio::test]
+        async fn increment_and_get_total_retries() {
+            let state = CrawlerState::default();
+
+            state.increment_total_fetcher_retries(2).await;
+            state.increment_total_persister_retries(1).await;
+
+            assert_eq!(2, state.get_total_fetcher_retries().await);
+            assert_eq!(1, state.get_total_persister_retries().await);
+        }
+
+        #[tokio::test]
+        async fn observe_and_get_request_latencies() {
+            let state = CrawlerState::default();
+
+            state
+                .observe_request_latency("search_organization", Duration::from_millis(100))
+                .await;
+            state
+                .observe_request_latency("search_organization", Duration::from_millis(200))
+                .await;
+            let request_latencies = state.get_request_latencies().await;
+
+            assert_eq!(1, request_latencies.len());
+            assert_eq!(2, request_latencies["search_organization"].count());
+        }
+
+        #[tokio::test]
+        async fn increment_seed_query_yields_of_assigned_organizations() {
+            let state = CrawlerState::default();
+
+            state
+                .assign_seed_query("query-1", &["org-1", "org-2"])
+                .await;
+            state.assign_seed_query("query-2", &["org-2"]).await;
+            state.assign_seed_query("query-3", &[]).await;
+            state.increment_seed_query_yield("org-1", 3).await;
+            state.increment_seed_query_yield("org-2", 2).await;
+            state.increment_seed_query_yield("org-3", 7).await;
+
+            assert_eq!(
+                BTreeMap::from([
+                    ("query-1".to_string(), 5),
+                    ("query-2".to_string(), 0),
+                    ("query-3".to_string(), 0),
+                ]),
+                state.get_seed_query_yields().await
+            );
+        }
+
         #[tokio::test]
         async fn increment_and_get_total_fetcher_calls() {
             let state = CrawlerState::default();
@@ -488,22 +1248,229 @@ mod tests {
         }
 
         #[tokio::test]
-        async fn update_and_get_current_api_rate_limit() {
+        async fn update_and_get_api_rate_limits() {
             let state = CrawlerState::default();
 
-            let rate_limit = FetcherRateLimit {
+            let rate_limit_1 = FetcherRateLimit {
                 limit: 5000,
                 cost: 1,
                 remaining: 4999,
                 reset_at: "2025-01-01T00:00:00Z".to_string(),
             };
+            let rate_limit_2 = FetcherRateLimit {
+                remaining: 4000,
+                ..rate_limit_1.clone()
+            };
 
             state
-                .update_current_api_rate_limit(rate_limit.clone())
+                .update_api_rate_limit("token-1", FetcherRateLimit::default())
+                .await;
+            state
+                .update_api_rate_limit("token-1", rate_limit_1.clone())
+                .await;
+            state
+                .update_api_rate_limit("token-2", rate_limit_2.clone())
                 .await;
-            let current_rate_limit = state.get_current_api_rate_limit().await;
+            let api_rate_limits = state.get_api_rate_limits().await;
+
+            assert_eq!(
+                BTreeMap::from([
+                    ("token-1".to_string(), rate_limit_1),
+                    ("token-2".to_string(), rate_limit_2)
+                ]),
+                api_rate_limits
+            );
+        }
 
-            assert_eq!(current_rate_limit, rate_limit);
+        #[tokio::test]
+        async fn pop_request_batch_coalesces_organization_repositories_requests() {
+            let state = CrawlerState::default();
+            let organization_request =
+                |name: &str| crate::OrganizationRepositoriesRequest::new(name, 10, None);
+            state
+                .push_requests(vec![
+                    Request::dummy_search_organization(),
+                    Request::OrganizationRepositories(organization_request("org-1")),
+                    Request::OrganizationRepositories(organization_request("org-2")),
+                    Request::OrganizationRepositories(organization_request("org-3")),
+                ])
+                .await;
+
+            let batch = state.pop_request_batch(2).await.unwrap();
+            let single = state.pop_request_batch(2).await.unwrap();
+
+            assert!(matches!(
+                &batch,
+                Request::OrganizationRepositoriesBatch(requests) if requests.len() == 2
+            ));
+            assert!(matches!(single, Request::OrganizationRepositories(_)));
+            assert_eq!(3, state.get_total_in_progress_requests().await);
+            state.acknowledge_request(&batch).await;
+            assert_eq!(1, state.get_total_in_progress_requests().await);
+            assert_eq!(
+                Some(Request::dummy_search_organization()),
+                state.pop_request_batch(2).await
+            );
+        }
+    }
+
+    mod crawler_state_shutdown {
+        use std::sync::Arc;
+
+        use super::*;
+
+        #[tokio::test]
+        async fn request_shutdown_wakes_up_waiters() {
+            let state = Arc::new(CrawlerState::default());
+            let waiter = {
+                let state = state.clone();
+                tokio::spawn(async move { state.wait_for_shutdown().await })
+            };
+
+            state.request_shutdown().await;
+
+            tokio::time::timeout(Duration::from_secs(1), waiter)
+                .await
+                .expect("Waiter should be woken up")
+                .unwrap();
+            assert!(state.is_shutdown_requested().await);
+        }
+
+        #[tokio::test]
+        async fn requeue_in_progress_requests() {
+            let state = CrawlerState::default();
+            let request = Request::dummy_search_organization();
+            state.push_request(request.clone()).await;
+            state.pop_request().await;
+
+            let total_requeued_requests = state.requeue_in_progress_requests().await;
+
+            assert_eq!(1, total_requeued_requests);
+            assert_eq!(Some(request), state.pop_request().await);
+        }
+    }
+
+    mod crawler_state_snapshot {
+        use super::*;
+
+        #[tokio::test]
+        async fn snapshot_and_restore_requeue_in_progress_requests() {
+            let state = CrawlerState::default();
+            state.set_total_repositories_target(100).await;
+            state.increment_total_fetcher_calls(3).await;
+            state.increment_total_persisted_repositories(20).await;
+            state.increment_total_collisions_repositories(2).await;
+            let request1 = Request::SearchOrganization(crate::SearchOrganizationRequest::new(
+                "org-1", 100, None,
+            ));
+            let request2 = Request::SearchOrganization(crate::SearchOrganizationRequest::new(
+                "org-2", 100, None,
+            ));
+            state
+                .push_requests(vec![request1.clone(), request2.clone()])
+                .await;
+            let in_progress_request = state.pop_request().await.unwrap();
+
+            let snapshot = state.snapshot().await;
+            let serialized_snapshot = serde_json::to_string(&snapshot).unwrap();
+            let restored_snapshot: CrawlerStateSnapshot =
+                serde_json::from_str(&serialized_snapshot).unwrap();
+            let restored_state = CrawlerState::from_snapshot(restored_snapshot);
+
+            assert_eq!(vec![in_progress_request], snapshot.requests_in_progress);
+            assert_eq!(100, restored_state.get_total_repositories_target().await);
+            assert_eq!(3, restored_state.get_total_fetcher_calls().await);
+            assert_eq!(20, restored_state.get_total_persisted_repositories().await);
+            assert_eq!(2, restored_state.get_total_collisions_repositories().await);
+            let restored_requests = HashSet::from([
+                restored_state.pop_request().await.unwrap(),
+                restored_state.pop_request().await.unwrap(),
+            ]);
+            assert_eq!(
+                HashSet::from([request1.clone(), request2]),
+                restored_requests
+            );
+            assert_eq!(None, restored_state.pop_request().await);
+            restored_state.push_request(request1).await;
+            assert_eq!(None, restored_state.pop_request().await);
+        }
+
+        #[tokio::test]
+        async fn snapshot_and_restore_keep_totals() {
+            let state = CrawlerState::default();
+            state.set_total_repositories_target(100).await;
+            state.increment_total_fetcher_calls(3).await;
+            state.increment_total_persisted_repositories(20).await;
+            state.increment_total_collisions_repositories(2).await;
+            state.increment_total_refreshed_repositories(4).await;
+            state.increment_total_unreachable_repositories(1).await;
+            state.increment_total_fetcher_retries(5).await;
+            state.increment_total_persister_retries(6).await;
+            state
+                .observe_request_latency("search_organization", Duration::from_millis(300))
+                .await;
+            state.increment_total_rate_limit_points_spent(7).await;
+            state.increment_total_parse_errors(8).await;
+            state.increment_total_node_errors(9).await;
+            state.increment_total_failed_requests(10).await;
+            state.assign_seed_query("query-1", &["org-1"]).await;
+            state.increment_seed_query_yield("org-1", 11).await;
+            state
+                .record_page_size_adjustment("search_organization", 50)
+                .await;
+            state
+                .push_request(Request::dummy_search_organization())
+                .await;
+            let started_at = Utc::now();
+
+            let serialized_snapshot = serde_json::to_string(&state.snapshot().await).unwrap();
+            let restored_state =
+                CrawlerState::from_snapshot(serde_json::from_str(&serialized_snapshot).unwrap());
+
+            assert_eq!(
+                state.state_summary().await,
+                restored_state.state_summary().await
+            );
+            assert_eq!(
+                crate::CrawlReport::from_state("crawl-1", &state, started_at, started_at, None)
+                    .await,
+                crate::CrawlReport::from_state(
+                    "crawl-1",
+                    &restored_state,
+                    started_at,
+                    started_at,
+                    None
+                )
+                .await
+            );
+            restored_state.increment_seed_query_yield("org-1", 1).await;
+            assert_eq!(
+                BTreeMap::from([("query-1".to_string(), 12)]),
+                restored_state.get_seed_query_yields().await
+            );
+        }
+
+        #[test]
+        fn restore_snapshot_of_earlier_version() {
+            let snapshot: CrawlerStateSnapshot = serde_json::from_str(
+                r#"{"requests_queued":[],"requests_pushed":[],"requests_in_progress":[],"total_repositories_target":100,"total_fetcher_calls":3,"total_persisted_repositories":20,"total_collisions_repositories":2}"#,
+            )
+            .unwrap();
+
+            assert_eq!(20, snapshot.total_persisted_repositories);
+            assert_eq!(0, snapshot.total_failed_requests);
+        }
+    }
+
+    mod owner_type {
+        use super::*;
+
+        #[test]
+        fn parse_owner_type_from_its_name() {
+            for owner_type in [OwnerType::Organization, OwnerType::User] {
+                assert_eq!(owner_type, owner_type.name().parse().unwrap());
+            }
+            assert!("enterprise".parse::<OwnerType>().is_err());
         }
     }
 
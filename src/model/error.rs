@@ -1,2 +1,350 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
 /// The standard result type used throughout the application.
 pub type StdResult<T> = Result<T, anyhow::Error>;
+
+/// The delay to wait after a rate limit without a `Retry-After` header, as advised by GitHub.
+const RATE_LIMIT_DEFAULT_DELAY: Duration = Duration::from_secs(60);
+
+/// How a failed operation is retried, decided by the class of its error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryPolicy {
+    /// The error is permanent and the operation fails fast.
+    FailFast,
+
+    /// The error is transient and the operation is retried with exponential backoff.
+    Backoff,
+
+    /// The operation is rate limited and retried after the given delay.
+    RetryAfter(Duration),
+}
+
+impl RetryPolicy {
+    /// Retrieves the retry policy of an error, retrying with exponential backoff the errors that are not classified.
+    pub fn of(error: &anyhow::Error) -> Self {
+        if let Some(error) = error.downcast_ref::<FetcherError>() {
+            error.retry_policy(Utc::now())
+        } else if let Some(error) = error.downcast_ref::<PersisterError>() {
+            error.retry_policy()
+        } else {
+            RetryPolicy::Backoff
+        }
+    }
+}
+
+/// The rate limit headers of an HTTP response of the GitHub API.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpRateLimitHeaders {
+    /// The delay to wait before retrying, from the `Retry-After` header.
+    pub retry_after: Option<Duration>,
+
+    /// The maximum number of points of the rate limit, from the `x-ratelimit-limit` header.
+    pub limit: Option<i32>,
+
+    /// The remaining points of the rate limit, from the `x-ratelimit-remaining` header.
+    pub remaining: Option<i32>,
+
+    /// The time at which the rate limit resets, from the `x-ratelimit-reset` header.
+    pub reset_at: Option<DateTime<Utc>>,
+}
+
+/// Fetcher error
+#[derive(Error, Debug)]
+pub enum FetcherError {
+    /// Authentication error, for bad credentials or missing permissions
+    #[error("Authentication error: {0}")]
+    Auth(String),
+    /// Credentials error, raised by the provider of the API tokens
+    #[error("Credentials error: {0}")]
+    Credentials(anyhow::Error),
+    /// Not found error
+    #[error("Not found error: {0}")]
+    NotFound(String),
+    /// Rate limit error, with the rate limit headers of the response
+    #[error("Rate limit error {status}: {message}")]
+    RateLimited {
+        status: u16,
+        headers: HttpRateLimitHeaders,
+        message: String,
+    },
+    /// Transient network error
+    #[error("Network error: {0}")]
+    Network(String),
+    /// Server error, for 5xx HTTP status
+    #[error("Server error {status}: {message}")]
+    Server { status: u16, message: String },
+    /// Client error, for the other 4xx HTTP status
+    #[error("HTTP error {status}: {message}")]
+    Http { status: u16, message: String },
+    /// Invalid GraphQL query
+    #[error("Query error: {0}")]
+    Query(String),
+    /// Search query that can not be split into partitions
+    #[error("Partition error: {0}")]
+    Partition(String),
+    /// GraphQL errors returned in place of the data of a response
+    #[error("GraphQL error: {0}")]
+    GraphQl(String),
+    /// Parse error
+    #[error("Parsing error: {0}")]
+    Parse(String),
+}
+
+impl FetcherError {
+    /// Classifies the error of an HTTP response from its status, headers and body.
+    pub fn from_http_status(status: u16, headers: HttpRateLimitHeaders, message: String) -> Self {
+        let is_rate_limited = status == 429
+            || (status == 403
+                && (headers.retry_after.is_some()
+                    || headers.remaining == Some(0)
+                    || message.to_lowercase().contains("rate limit")));
+        match status {
+            _ if is_rate_limited => FetcherError::RateLimited {
+                status,
+                headers,
+                message,
+            },
+            401 | 403 => FetcherError::Auth(message),
+            404 => FetcherError::NotFound(message),
+            500..=599 => FetcherError::Server { status, message },
+            _ => FetcherError::Http { status, message },
+        }
+    }
+
+    /// Retrieves the delay to wait before retrying after a rate limit, following the advice of the GitHub API.
+    pub fn retry_delay(&self, now: DateTime<Utc>) -> Option<Duration> {
+        let FetcherError::RateLimited { headers, .. } = self else {
+            return None;
+        };
+        if let Some(retry_after) = headers.retry_after {
+            return Some(retry_after);
+        }
+        if headers.remaining == Some(0)
+            && let Some(reset_at) = headers.reset_at
+        {
+            return Some((reset_at - now).to_std().unwrap_or_default());
+        }
+
+        Some(RATE_LIMIT_DEFAULT_DELAY)
+    }
+
+    /// Retrieves the retry policy of the error.
+    pub fn retry_policy(&self, now: DateTime<Utc>) -> RetryPolicy {
+        match self {
+            FetcherError::RateLimited { .. } => {
+                RetryPolicy::RetryAfter(self.retry_delay(now).unwrap_or(RATE_LIMIT_DEFAULT_DELAY))
+            }
+            FetcherError::Credentials(_)
+            | FetcherError::Network(_)
+            | FetcherError::Server { .. }
+            | FetcherError::GraphQl(_) => RetryPolicy::Backoff,
+            FetcherError::Auth(_)
+            | FetcherError::NotFound(_)
+            | FetcherError::Http { .. }
+            | FetcherError::Query(_)
+            | FetcherError::Partition(_)
+            | FetcherError::Parse(_) => RetryPolicy::FailFast,
+        }
+    }
+}
+
+/// Persister error
+#[derive(Error, Debug)]
+pub enum PersisterError {
+    /// Constraint error, for data violating a constraint of the storage
+    #[error("Constraint error: {0}")]
+    Constraint(String),
+    /// Connection error, for a storage that is unreachable or temporarily unavailable
+    #[error("Connection error: {0}")]
+    Connection(String),
+    /// Query error, for the other errors raised by the storage
+    #[error("Query error: {0}")]
+    Query(String),
+}
+
+impl PersisterError {
+    /// Retrieves the retry policy of the error.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        match self {
+            PersisterError::Connection(_) => RetryPolicy::Backoff,
+            PersisterError::Constraint(_) | PersisterError::Query(_) => RetryPolicy::FailFast,
+        }
+    }
+}
+
+impl From<sqlx::Error> for PersisterError {
+    fn from(error: sqlx::Error) -> Self {
+        match &error {
+            sqlx::Error::Database(database_error) => {
+                let code = database_error.code().unwrap_or_default();
+                // The extended SQLite result codes keep the primary result code in their lower byte
+                let is_sqlite_busy_or_locked = database_error
+                    .try_downcast_ref::<sqlx::sqlite::SqliteError>()
+                    .and_then(|_| code.parse::<i32>().ok())
+                    .is_some_and(|code| matches!(code & 0xff, 5 | 6));
+                if !matches!(database_error.kind(), sqlx::error::ErrorKind::Other) {
+                    PersisterError::Constraint(error.to_string())
+                } else if code.starts_with("08")
+                    || code.starts_with("53")
+                    || code.starts_with("57P")
+                    || matches!(code.as_ref(), "40001" | "40P01")
+                    || is_sqlite_busy_or_locked
+                {
+                    // PostgreSQL connection, resources, shutdown, serialization and deadlock errors,
+                    // and SQLite busy and locked errors
+                    PersisterError::Connection(error.to_string())
+                } else {
+                    PersisterError::Query(error.to_string())
+                }
+            }
+            sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::Protocol(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed => PersisterError::Connection(error.to_string()),
+            _ => PersisterError::Query(error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod fetcher_error {
+        use super::*;
+
+        #[test]
+        fn classify_http_status() {
+            let classify = |status: u16, headers: HttpRateLimitHeaders, message: &str| {
+                FetcherError::from_http_status(status, headers, message.to_string())
+            };
+
+            assert!(matches!(
+                classify(401, HttpRateLimitHeaders::default(), "Bad credentials"),
+                FetcherError::Auth(_)
+            ));
+            assert!(matches!(
+                classify(403, HttpRateLimitHeaders::default(), "Forbidden"),
+                FetcherError::Auth(_)
+            ));
+            assert!(matches!(
+                classify(
+                    403,
+                    HttpRateLimitHeaders::default(),
+                    "You have exceeded a secondary rate limit"
+                ),
+                FetcherError::RateLimited { .. }
+            ));
+            assert!(matches!(
+                classify(404, HttpRateLimitHeaders::default(), "Not Found"),
+                FetcherError::NotFound(_)
+            ));
+            assert!(matches!(
+                classify(502, HttpRateLimitHeaders::default(), "Bad Gateway"),
+                FetcherError::Server { status: 502, .. }
+            ));
+            assert!(matches!(
+                classify(422, HttpRateLimitHeaders::default(), "Unprocessable"),
+                FetcherError::Http { status: 422, .. }
+            ));
+        }
+
+        #[test]
+        fn retry_delay_from_retry_after_header() {
+            let error = FetcherError::from_http_status(
+                403,
+                HttpRateLimitHeaders {
+                    retry_after: Some(Duration::from_secs(42)),
+                    ..Default::default()
+                },
+                "You have exceeded a secondary rate limit".to_string(),
+            );
+
+            assert_eq!(
+                RetryPolicy::RetryAfter(Duration::from_secs(42)),
+                error.retry_policy(Utc::now())
+            );
+        }
+
+        #[test]
+        fn retry_delay_until_rate_limit_reset() {
+            let now = Utc::now();
+            let error = FetcherError::from_http_status(
+                429,
+                HttpRateLimitHeaders {
+                    remaining: Some(0),
+                    reset_at: Some(now + chrono::Duration::seconds(30)),
+                    ..Default::default()
+                },
+                String::new(),
+            );
+
+            assert_eq!(Some(Duration::from_secs(30)), error.retry_delay(now));
+        }
+
+        #[test]
+        fn retry_delay_of_rate_limit_without_headers() {
+            let error = FetcherError::from_http_status(
+                403,
+                HttpRateLimitHeaders::default(),
+                "You have exceeded a secondary rate limit".to_string(),
+            );
+
+            assert_eq!(
+                Some(RATE_LIMIT_DEFAULT_DELAY),
+                error.retry_delay(Utc::now())
+            );
+        }
+
+        #[test]
+        fn retry_policy_of_transient_and_permanent_errors() {
+            let now = Utc::now();
+
+            assert_eq!(
+                RetryPolicy::Backoff,
+                FetcherError::Network("timeout".to_string()).retry_policy(now)
+            );
+            assert_eq!(
+                RetryPolicy::Backoff,
+                FetcherError::GraphQl("Something went wrong".to_string()).retry_policy(now)
+            );
+            assert_eq!(
+                RetryPolicy::FailFast,
+                FetcherError::Auth("Bad credentials".to_string()).retry_policy(now)
+            );
+            assert_eq!(
+                RetryPolicy::FailFast,
+                FetcherError::Query("Field 'foo' doesn't exist".to_string()).retry_policy(now)
+            );
+            assert_eq!(None, FetcherError::Parse("".to_string()).retry_delay(now));
+        }
+    }
+
+    mod retry_policy {
+        use anyhow::anyhow;
+
+        use super::*;
+
+        #[test]
+        fn retry_policy_of_classified_and_unclassified_errors() {
+            assert_eq!(
+                RetryPolicy::FailFast,
+                RetryPolicy::of(&anyhow!(FetcherError::NotFound("".to_string())))
+            );
+            assert_eq!(
+                RetryPolicy::FailFast,
+                RetryPolicy::of(&anyhow!(PersisterError::Constraint("".to_string())))
+            );
+            assert_eq!(
+                RetryPolicy::Backoff,
+                RetryPolicy::of(&anyhow!(PersisterError::Connection("".to_string())))
+            );
+            assert_eq!(RetryPolicy::Backoff, RetryPolicy::of(&anyhow!("Unknown")));
+        }
+    }
+}
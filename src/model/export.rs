@@ -0,0 +1,246 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::Repository;
+
+/// A column of the exported repositories.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportColumn {
+    /// The name of the repository.
+    RepositoryName,
+
+    /// The name of the organization owning the repository.
+    OrganizationName,
+
+    /// The stars count of the repository.
+    TotalStars,
+
+    /// The GraphQL node identifier of the repository.
+    NodeId,
+
+    /// The database identifier of the repository.
+    DatabaseId,
+
+    /// The primary language of the repository.
+    PrimaryLanguage,
+
+    /// The number of forks of the repository.
+    ForkCount,
+
+    /// Whether the repository is a fork.
+    IsFork,
+
+    /// Whether the repository is archived.
+    IsArchived,
+
+    /// The creation time of the repository.
+    CreatedAt,
+
+    /// The time of the latest push to the repository.
+    PushedAt,
+
+    /// The SPDX identifier of the license of the repository.
+    License,
+
+    /// The description of the repository.
+    Description,
+
+    /// The topics of the repository.
+    Topics,
+
+    /// The type of the account owning the repository, `organization` or `user`.
+    OwnerType,
+}
+
+impl ExportColumn {
+    /// Retrieves the name of the column.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportColumn::RepositoryName => "repository_name",
+            ExportColumn::OrganizationName => "organization_name",
+            ExportColumn::TotalStars => "total_stars",
+            ExportColumn::NodeId => "node_id",
+            ExportColumn::DatabaseId => "database_id",
+            ExportColumn::PrimaryLanguage => "primary_language",
+            ExportColumn::ForkCount => "fork_count",
+            ExportColumn::IsFork => "is_fork",
+            ExportColumn::IsArchived => "is_archived",
+            ExportColumn::CreatedAt => "created_at",
+            ExportColumn::PushedAt => "pushed_at",
+            ExportColumn::License => "license",
+            ExportColumn::Description => "description",
+            ExportColumn::Topics => "topics",
+            ExportColumn::OwnerType => "owner_type",
+        }
+    }
+
+    /// Retrieves the value of the column for the given repository.
+    pub fn value(&self, repository: &Repository) -> ExportValue {
+        let metadata = repository.metadata();
+        match self {
+            ExportColumn::RepositoryName => {
+                ExportValue::Text(Some(repository.repository_name().to_string()))
+            }
+            ExportColumn::OrganizationName => {
+                ExportValue::Text(Some(repository.organization_name().to_string()))
+            }
+            ExportColumn::TotalStars => {
+                ExportValue::Integer(Some(**repository.total_stars() as i64))
+            }
+            ExportColumn::NodeId => {
+                ExportValue::Text(repository.node_id().map(|node_id| node_id.to_string()))
+            }
+            ExportColumn::DatabaseId => ExportValue::Integer(metadata.database_id),
+            ExportColumn::PrimaryLanguage => ExportValue::Text(metadata.primary_language.clone()),
+            ExportColumn::ForkCount => ExportValue::Integer(metadata.fork_count.map(i64::from)),
+            ExportColumn::IsFork => ExportValue::Boolean(metadata.is_fork),
+            ExportColumn::IsArchived => ExportValue::Boolean(metadata.is_archived),
+            ExportColumn::CreatedAt => ExportValue::Timestamp(metadata.created_at),
+            ExportColumn::PushedAt => ExportValue::Timestamp(metadata.pushed_at),
+            ExportColumn::License => ExportValue::Text(metadata.license.clone()),
+            ExportColumn::Description => ExportValue::Text(metadata.description.clone()),
+            ExportColumn::Topics => ExportValue::TextList(metadata.topics.clone()),
+            ExportColumn::OwnerType => ExportValue::Text(
+                repository
+                    .owner_type()
+                    .map(|owner_type| owner_type.to_string()),
+            ),
+        }
+    }
+}
+
+/// A typed value of an exported column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportValue {
+    /// A text value.
+    Text(Option<String>),
+
+    /// An integer value.
+    Integer(Option<i64>),
+
+    /// A boolean value.
+    Boolean(Option<bool>),
+
+    /// A timestamp value.
+    Timestamp(Option<DateTime<Utc>>),
+
+    /// A list of text values.
+    TextList(Option<Vec<String>>),
+}
+
+impl ExportValue {
+    /// Converts the value to a JSON value.
+    pub fn to_json(&self) -> Value {
+        match self {
+            ExportValue::Text(value) => value.clone().map_or(Value::Null, Value::from),
+            ExportValue::Integer(value) => value.map_or(Value::Null, Value::from),
+            ExportValue::Boolean(value) => value.map_or(Value::Null, Value::from),
+            ExportValue::Timestamp(value) => {
+                value.map_or(Value::Null, |value| Value::from(value.to_rfc3339()))
+            }
+            ExportValue::TextList(value) => value.clone().map_or(Value::Null, Value::from),
+        }
+    }
+
+    /// Converts the value to a text field, empty if the value is missing.
+    ///
+    /// The items of a list are separated with a semicolon.
+    pub fn to_text(&self) -> String {
+        match self {
+            ExportValue::Text(value) => value.clone().unwrap_or_default(),
+            ExportValue::Integer(value) => value.map(|value| value.to_string()).unwrap_or_default(),
+            ExportValue::Boolean(value) => value.map(|value| value.to_string()).unwrap_or_default(),
+            ExportValue::Timestamp(value) => {
+                value.map(|value| value.to_rfc3339()).unwrap_or_default()
+            }
+            ExportValue::TextList(value) => value
+                .as_ref()
+                .map(|value| value.join(";"))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The ordering of the exported repositories.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepositoryOrder {
+    /// Descending stars count.
+    Stars,
+}
+
+/// A filter to select the repositories to read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepositoryFilter {
+    /// Only select the repositories of this organization.
+    pub organization_name: Option<String>,
+
+    /// Only select the repositories with at least this stars count.
+    pub min_stars: Option<u32>,
+
+    /// The ordering of the repositories.
+    pub order_by: Option<RepositoryOrder>,
+
+    /// The maximum number of repositories.
+    pub limit: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RepositoryMetadata;
+
+    use super::*;
+
+    fn repository() -> Repository {
+        Repository::new("repository-1", "org-1", 100)
+            .with_node_id("R_1")
+            .with_metadata(RepositoryMetadata {
+                is_fork: Some(false),
+                topics: Some(vec!["rust".to_string(), "cli".to_string()]),
+                ..RepositoryMetadata::default()
+            })
+    }
+
+    #[test]
+    fn value_to_json() {
+        let repository = repository();
+
+        assert_eq!(
+            vec![
+                Value::from("repository-1"),
+                Value::from(100),
+                Value::from(false),
+                Value::Null,
+                Value::from(vec!["rust", "cli"]),
+            ],
+            [
+                ExportColumn::RepositoryName,
+                ExportColumn::TotalStars,
+                ExportColumn::IsFork,
+                ExportColumn::License,
+                ExportColumn::Topics
+            ]
+            .iter()
+            .map(|column| column.value(&repository).to_json())
+            .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn value_to_text() {
+        let repository = repository();
+
+        assert_eq!(
+            vec!["R_1", "100", "false", "", "rust;cli"],
+            [
+                ExportColumn::NodeId,
+                ExportColumn::TotalStars,
+                ExportColumn::IsFork,
+                ExportColumn::License,
+                ExportColumn::Topics
+            ]
+            .iter()
+            .map(|column| column.value(&repository).to_text())
+            .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// The upper bounds, in seconds, of the buckets of the latency histograms.
+pub const LATENCY_HISTOGRAM_BUCKETS: [f64; 11] =
+    [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0];
+
+/// A histogram of latencies with the fixed buckets of `LATENCY_HISTOGRAM_BUCKETS`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct LatencyHistogram {
+    /// The number of observations in each bucket (not cumulative).
+    bucket_counts: [u64; LATENCY_HISTOGRAM_BUCKETS.len()],
+
+    /// The sum of the observed latencies, in seconds.
+    sum_seconds: f64,
+
+    /// The number of observations.
+    count: u64,
+}
+
+impl LatencyHistogram {
+    /// Records an observed latency.
+    pub fn observe(&mut self, latency: Duration) {
+        let latency_seconds = latency.as_secs_f64();
+        if let Some(bucket_index) = LATENCY_HISTOGRAM_BUCKETS
+            .iter()
+            .position(|upper_bound| latency_seconds <= *upper_bound)
+        {
+            self.bucket_counts[bucket_index] += 1;
+        }
+        self.sum_seconds += latency_seconds;
+        self.count += 1;
+    }
+
+    /// Retrieves the cumulative number of observations for each bucket upper bound.
+    pub fn cumulative_buckets(&self) -> Vec<(f64, u64)> {
+        LATENCY_HISTOGRAM_BUCKETS
+            .iter()
+            .zip(self.bucket_counts.iter())
+            .scan(0, |cumulative_count, (upper_bound, bucket_count)| {
+                *cumulative_count += bucket_count;
+                Some((*upper_bound, *cumulative_count))
+            })
+            .collect()
+    }
+
+    /// Retrieves the sum of the observed latencies, in seconds.
+    pub fn sum_seconds(&self) -> f64 {
+        self.sum_seconds
+    }
+
+    /// Retrieves the number of observations.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn observe_latencies_in_cumulative_buckets() {
+        let mut histogram = LatencyHistogram::default();
+
+        histogram.observe(Duration::from_millis(40));
+        histogram.observe(Duration::from_millis(300));
+        histogram.observe(Duration::from_secs(1000));
+
+        let cumulative_buckets = histogram.cumulative_buckets();
+        assert_eq!((0.05, 1), cumulative_buckets[0]);
+        assert_eq!((0.25, 1), cumulative_buckets[2]);
+        assert_eq!((0.5, 2), cumulative_buckets[3]);
+        assert_eq!((300.0, 2), cumulative_buckets[10]);
+        assert_eq!(3, histogram.count());
+        assert!((histogram.sum_seconds() - 1000.34).abs() < 1e-9);
+    }
+}
@@ -1,9 +1,15 @@
 mod entities;
 mod error;
+mod export;
+mod metrics;
+mod report;
 mod request;
 mod response;
 
 pub use entities::*;
 pub use error::*;
+pub use export::*;
+pub use metrics::*;
+pub use report::*;
 pub use request::*;
 pub use response::*;
@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::CrawlerState;
+
+/// The outcome of a crawl run.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CrawlStatus {
+    /// The crawl persisted the target number of repositories or exhausted its requests.
+    Completed,
+
+    /// The crawl was stopped by a shutdown signal and can be resumed.
+    Interrupted,
+
+    /// The crawl stopped on an error.
+    Failed,
+}
+
+/// A machine-readable report of a crawl run, written at the end of the run.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CrawlReport {
+    /// The identifier of the crawl.
+    pub crawl_id: String,
+
+    /// The outcome of the crawl run.
+    pub status: CrawlStatus,
+
+    /// The reason of the failure of the crawl run, if it failed.
+    pub failure_reason: Option<String>,
+
+    /// The time at which the crawl run started.
+    pub started_at: DateTime<Utc>,
+
+    /// The time at which the crawl run ended.
+    pub finished_at: DateTime<Utc>,
+
+    /// The total number of repositories to be fetched.
+    pub total_repositories_target: u32,
+
+    /// The total number of repositories persisted.
+    pub total_persisted_repositories: u32,
+
+    /// The total number of repositories that already existed.
+    pub total_collisions_repositories: u32,
+
+    /// The total number of known repositories refreshed.
+    pub total_refreshed_repositories: u32,
+
+    /// The total number of known repositories marked as deleted or inaccessible.
+    pub total_unreachable_repositories: u32,
+
+    /// The number of repositories fetched through each seed query.
+    pub seed_query_yields: BTreeMap<String, u32>,
+
+    /// The number of requests made, by request kind.
+    pub total_requests: BTreeMap<String, u64>,
+
+    /// The total number of fetch attempts that were retried.
+    pub total_fetcher_retries: u32,
+
+    /// The total number of persist attempts that were retried.
+    pub total_persister_retries: u32,
+
+    /// The total number of API rate limit points spent.
+    pub total_rate_limit_points_spent: u32,
+
+    /// The total number of responses and search results skipped because they could not be parsed.
+    pub total_parse_errors: u32,
+
+    /// The total number of node-level errors of partial GraphQL responses.
+    pub total_node_errors: u32,
+
+    /// The total number of requests skipped after a permanent error.
+    pub total_failed_requests: u32,
+
+    /// The page size adapted for the requests, by request kind.
+    pub page_sizes: BTreeMap<String, u16>,
+
+    /// The total number of adjustments of the page size of the requests.
+    pub total_page_size_adjustments: u32,
+}
+
+impl CrawlReport {
+    /// Creates a new `CrawlReport` instance from the state of the crawler and the result of the crawl.
+    pub async fn from_state(
+        crawl_id: &str,
+        state: &CrawlerState,
+        started_at: DateTime<Utc>,
+        finished_at: DateTime<Utc>,
+        failure: Option<&anyhow::Error>,
+    ) -> Self {
+        let status = match failure {
+            Some(_) => CrawlStatus::Failed,
+            None if state.is_shutdown_requested().await => CrawlStatus::Interrupted,
+            None => CrawlStatus::Completed,
+        };
+
+        Self {
+            crawl_id: crawl_id.to_string(),
+            status,
+            failure_reason: failure.map(|e| format!("{e:#}")),
+            started_at,
+            finished_at,
+            total_repositories_target: state.get_total_repositories_target().await,
+            total_persisted_repositories: state.get_total_persisted_repositories().await,
+            total_collisions_repositories: state.get_total_collisions_repositories().await,
+            total_refreshed_repositories: state.get_total_refreshed_repositories().await,
+            total_unreachable_repositories: state.get_total_unreachable_repositories().await,
+            seed_query_yields: state.get_seed_query_yields().await,
+            total_requests: state
+                .get_request_latencies()
+                .await
+                .into_iter()
+                .map(|(request_kind, histogram)| (request_kind, histogram.count()))
+                .collect(),
+            total_fetcher_retries: state.get_total_fetcher_retries().await,
+            total_persister_retries: state.get_total_persister_retries().await,
+            total_rate_limit_points_spent: state.get_total_rate_limit_points_spent().await,
+            total_parse_errors: state.get_total_parse_errors().await,
+            total_node_errors: state.get_total_node_errors().await,
+            total_failed_requests: state.get_total_failed_requests().await,
+            page_sizes: state.get_page_sizes().await,
+            total_page_size_adjustments: state.get_total_page_size_adjustments().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use anyhow::anyhow;
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn report_from_state_of_failed_crawl() {
+        let state = CrawlerState::default();
+        state.set_total_repositories_target(10).await;
+        state.increment_total_persisted_repositories(4).await;
+        state.assign_seed_query("query-1", &["org-1"]).await;
+        state.increment_seed_query_yield("org-1", 4).await;
+        state
+            .observe_request_latency("search_organization", Duration::from_millis(100))
+            .await;
+        state.increment_total_rate_limit_points_spent(1).await;
+        state
+            .record_page_size_adjustment("search_organization", 50)
+            .await;
+        let started_at = "2025-01-01T00:00:00Z".parse().unwrap();
+        let finished_at = "2025-01-01T01:00:00Z".parse().unwrap();
+
+        let report = CrawlReport::from_state(
+            "crawl-1",
+            &state,
+            started_at,
+            finished_at,
+            Some(&anyhow!("Crawler failed")),
+        )
+        .await;
+
+        assert_eq!(
+            json!({
+                "crawl_id": "crawl-1",
+                "status": "failed",
+                "failure_reason": "Crawler failed",
+                "started_at": "2025-01-01T00:00:00Z",
+                "finished_at": "2025-01-01T01:00:00Z",
+                "total_repositories_target": 10,
+                "total_persisted_repositories": 4,
+                "total_collisions_repositories": 0,
+                "total_refreshed_repositories": 0,
+                "total_unreachable_repositories": 0,
+                "seed_query_yields": {"query-1": 4},
+                "total_requests": {"search_organization": 1},
+                "total_fetcher_retries": 0,
+                "total_persister_retries": 0,
+                "total_rate_limit_points_spent": 1,
+                "total_parse_errors": 0,
+                "total_node_errors": 0,
+                "total_failed_requests": 0,
+                "page_sizes": {"search_organization": 50},
+                "total_page_size_adjustments": 1,
+            }),
+            serde_json::to_value(&report).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn report_from_state_of_interrupted_crawl() {
+        let state = CrawlerState::default();
+        state.request_shutdown().await;
+
+        let report = CrawlReport::from_state("crawl-1", &state, Utc::now(), Utc::now(), None).await;
+
+        assert_eq!(CrawlStatus::Interrupted, report.status);
+        assert_eq!(None, report.failure_reason);
+    }
+}
@@ -1,36 +1,125 @@
-use std::{cmp::Ordering, fmt::Display};
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
 
-use serde::Serialize;
+use anyhow::anyhow;
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::StdResult;
+
+/// The creation date of the oldest repositories on GitHub.
+const GITHUB_FIRST_REPOSITORY_DATE: NaiveDate = NaiveDate::from_ymd_opt(2007, 10, 1).unwrap();
 
 /// A request to the GitHub API
-#[derive(Debug, Serialize, PartialEq, Eq, Clone, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Hash)]
 pub enum Request {
     /// A request to fetch organizations from the GitHub API.
     SearchOrganization(SearchOrganizationRequest),
 
     /// A request to fetch repository metadata from the GitHub API for a specific organization.
     RepositoriesFromOrganization(RepositoriesFromOrganizationRequest),
+
+    /// A request to fetch repository metadata from the GitHub API for a specific user.
+    RepositoriesFromUser(RepositoriesFromUserRequest),
+
+    /// A request to walk the repositories connection of a specific organization on the GitHub API.
+    OrganizationRepositories(OrganizationRepositoriesRequest),
+
+    /// A batch of requests walking the repositories connections of several organizations in a single query.
+    OrganizationRepositoriesBatch(Vec<OrganizationRepositoriesRequest>),
+
+    /// A request to fetch again known repositories from the GitHub API by their node identifiers.
+    RefreshRepositories(RefreshRepositoriesRequest),
 }
 
 impl Request {
-    fn get_first(&self) -> u16 {
+    /// Retrieves the number of results requested per page, the largest one for a batch.
+    pub fn get_first(&self) -> u16 {
         match self {
             Request::SearchOrganization(request) => request.first,
             Request::RepositoriesFromOrganization(request) => request.first,
+            Request::RepositoriesFromUser(request) => request.first,
+            Request::OrganizationRepositories(request) => request.first,
+            Request::OrganizationRepositoriesBatch(requests) => requests
+                .iter()
+                .map(|request| request.first)
+                .max()
+                .unwrap_or_default(),
+            Request::RefreshRepositories(request) => request.node_ids.len() as u16,
         }
     }
 
+    /// Sets the number of results requested per page, for each request of a batch.
+    ///
+    /// The page size of a refresh request is set by its node identifiers and left unchanged.
+    pub fn with_first(mut self, first: u16) -> Self {
+        match &mut self {
+            Request::SearchOrganization(request) => request.first = first,
+            Request::RepositoriesFromOrganization(request) => request.first = first,
+            Request::RepositoriesFromUser(request) => request.first = first,
+            Request::OrganizationRepositories(request) => request.first = first,
+            Request::OrganizationRepositoriesBatch(requests) => {
+                for request in requests {
+                    request.first = first;
+                }
+            }
+            Request::RefreshRepositories(_) => {}
+        }
+        self
+    }
+
     fn get_after(&self) -> Option<String> {
         match self {
             Request::SearchOrganization(request) => request.after.clone(),
             Request::RepositoriesFromOrganization(request) => request.after.clone(),
+            Request::RepositoriesFromUser(request) => request.after.clone(),
+            Request::OrganizationRepositories(request) => request.after.clone(),
+            Request::OrganizationRepositoriesBatch(_) | Request::RefreshRepositories(_) => None,
         }
     }
 
     fn get_variant_weight(&self) -> u16 {
         match self {
             Request::SearchOrganization(_) => 0,
-            Request::RepositoriesFromOrganization(_) => 1,
+            Request::RepositoriesFromOrganization(_)
+            | Request::RepositoriesFromUser(_)
+            | Request::OrganizationRepositories(_)
+            | Request::OrganizationRepositoriesBatch(_)
+            | Request::RefreshRepositories(_) => 1,
+        }
+    }
+
+    /// Retrieves the kind of the request, used to label its metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Request::SearchOrganization(_) => "search_organization",
+            Request::RepositoriesFromOrganization(_) => "repositories_from_organization",
+            Request::RepositoriesFromUser(_) => "repositories_from_user",
+            Request::OrganizationRepositories(_) => "organization_repositories",
+            Request::OrganizationRepositoriesBatch(_) => "organization_repositories_batch",
+            Request::RefreshRepositories(_) => "refresh_repositories",
+        }
+    }
+
+    /// Retrieves the name of the account owning the repositories requested, if any.
+    pub fn owner_name(&self) -> Option<&str> {
+        match self {
+            Request::SearchOrganization(_) => None,
+            Request::RepositoriesFromOrganization(request) => Some(&request.organization_name),
+            Request::RepositoriesFromUser(request) => Some(&request.user_name),
+            Request::OrganizationRepositories(request) => Some(&request.organization_name),
+            Request::OrganizationRepositoriesBatch(_) | Request::RefreshRepositories(_) => None,
+        }
+    }
+
+    /// Splits a batch into the requests it is made of.
+    pub fn unbatch(&self) -> Vec<Request> {
+        match self {
+            Request::OrganizationRepositoriesBatch(requests) => requests
+                .iter()
+                .cloned()
+                .map(Request::OrganizationRepositories)
+                .collect(),
+            request => vec![request.clone()],
         }
     }
 
@@ -60,6 +149,15 @@ impl Ord for Request {
                 Request::RepositoriesFromOrganization(request) => request
                     .organization_name
                     .cmp(&other.get_after().unwrap_or_default()),
+                Request::RepositoriesFromUser(request) => request
+                    .user_name
+                    .cmp(&other.get_after().unwrap_or_default()),
+                Request::OrganizationRepositories(request) => request
+                    .organization_name
+                    .cmp(&other.get_after().unwrap_or_default()),
+                Request::OrganizationRepositoriesBatch(_) | Request::RefreshRepositories(_) => {
+                    self.to_string().cmp(&other.to_string())
+                }
             })
     }
 }
@@ -69,12 +167,24 @@ impl Display for Request {
         match self {
             Request::SearchOrganization(request) => write!(f, "{}", request),
             Request::RepositoriesFromOrganization(request) => write!(f, "{}", request),
+            Request::RepositoriesFromUser(request) => write!(f, "{}", request),
+            Request::OrganizationRepositories(request) => write!(f, "{}", request),
+            Request::OrganizationRepositoriesBatch(requests) => write!(
+                f,
+                "OrganizationRepositoriesBatch: organization_names=[{}]",
+                requests
+                    .iter()
+                    .map(|request| request.organization_name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Request::RefreshRepositories(request) => write!(f, "{}", request),
         }
     }
 }
 
 /// A search request being made to the GitHub API
-#[derive(Debug, Serialize, PartialEq, Eq, Clone, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Hash)]
 pub struct SearchOrganizationRequest {
     /// The text query.
     pub(crate) query: String,
@@ -84,6 +194,9 @@ pub struct SearchOrganizationRequest {
 
     /// The cursor for pagination.
     pub(crate) after: Option<String>,
+
+    /// The partition of the search space covered by the request.
+    pub(crate) partition: SearchPartition,
 }
 
 impl SearchOrganizationRequest {
@@ -93,9 +206,39 @@ impl SearchOrganizationRequest {
             query: query.to_string(),
             first,
             after,
+            partition: SearchPartition::default(),
         }
     }
 
+    /// Restricts the request to the given partition of the search space.
+    pub fn with_partition(mut self, partition: SearchPartition) -> Self {
+        self.partition = partition;
+        self
+    }
+
+    /// Retrieves the search query string, the qualifiers of the partition replacing the ones of the query.
+    pub fn search_query(&self) -> String {
+        let terms = self
+            .query
+            .split_whitespace()
+            .filter(|term| !(self.partition.created.is_some() && term.starts_with("created:")))
+            .filter(|term| !(self.partition.stars.is_some() && term.starts_with("stars:")))
+            .collect::<Vec<_>>();
+
+        format!("{}{}", terms.join(" "), self.partition.qualifiers())
+    }
+
+    /// Splits the partition of the request, within the creation dates and stars ranges of the qualifiers of its query.
+    pub fn split_partition(&self, today: NaiveDate) -> StdResult<Option<Vec<SearchPartition>>> {
+        let query_partition = SearchPartition::try_from_query(&self.query, today)?;
+        let partition = SearchPartition::new(
+            self.partition.created.or(query_partition.created),
+            self.partition.stars.or(query_partition.stars),
+        );
+
+        Ok(partition.split(today))
+    }
+
     /// Creates a dummy `SearchOrganizationRequest` for testing purposes.
     #[cfg(test)]
     pub(crate) fn dummy() -> Self {
@@ -103,6 +246,7 @@ impl SearchOrganizationRequest {
             query: "dummy".to_string(),
             first: 10,
             after: None,
+            partition: SearchPartition::default(),
         }
     }
 }
@@ -112,13 +256,155 @@ impl Display for SearchOrganizationRequest {
         write!(
             f,
             "SearchOrganizationRequest: query={}, first={}, after={:?}",
-            self.query, self.first, self.after
+            self.search_query(),
+            self.first,
+            self.after
+        )
+    }
+}
+
+/// A partition of the GitHub search space, used to keep the results of a search below the cap of the API.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Hash, Default)]
+pub struct SearchPartition {
+    /// The window of creation dates (inclusive) of the repositories.
+    pub(crate) created: Option<(NaiveDate, NaiveDate)>,
+
+    /// The band of stars (inclusive) of the repositories, open-ended without upper bound.
+    pub(crate) stars: Option<(u32, Option<u32>)>,
+}
+
+impl SearchPartition {
+    /// Creates a new `SearchPartition` with the given creation dates window and stars band.
+    pub fn new(created: Option<(NaiveDate, NaiveDate)>, stars: Option<(u32, Option<u32>)>) -> Self {
+        Self { created, stars }
+    }
+
+    /// Creates a new `SearchPartition` from the `created:` and `stars:` qualifiers of a search query.
+    ///
+    /// Fails if a qualifier is not an inclusive range, a comparison or a single value that can be partitioned.
+    pub fn try_from_query(query: &str, today: NaiveDate) -> StdResult<Self> {
+        let mut partition = Self::default();
+        for term in query.split_whitespace() {
+            if let Some(value) = term.strip_prefix("created:") {
+                partition.created = Some(
+                    parse_qualifier_range(
+                        value,
+                        GITHUB_FIRST_REPOSITORY_DATE,
+                        |date: NaiveDate| date.succ_opt(),
+                        |date: NaiveDate| date.pred_opt(),
+                    )
+                    .map(|(from, to)| (from, to.unwrap_or(today)))
+                    .filter(|(from, to)| from <= to)
+                    .ok_or_else(|| anyhow!("Unsupported qualifier in search query: {term}"))?,
+                );
+            } else if let Some(value) = term.strip_prefix("stars:") {
+                partition.stars = Some(
+                    parse_qualifier_range(
+                        value,
+                        0,
+                        |stars: u32| stars.checked_add(1),
+                        |stars: u32| stars.checked_sub(1),
+                    )
+                    .ok_or_else(|| anyhow!("Unsupported qualifier in search query: {term}"))?,
+                );
+            }
+        }
+
+        Ok(partition)
+    }
+
+    /// Retrieves the search qualifiers matching the partition.
+    pub fn qualifiers(&self) -> String {
+        let mut qualifiers = String::new();
+        if let Some((from, to)) = self.created {
+            qualifiers.push_str(&format!(" created:{from}..{to}"));
+        }
+        match self.stars {
+            Some((from, Some(to))) => qualifiers.push_str(&format!(" stars:{from}..{to}")),
+            Some((from, None)) => qualifiers.push_str(&format!(" stars:>={from}")),
+            None => {}
+        }
+
+        qualifiers
+    }
+
+    /// Splits the partition in two sub-partitions, first by creation dates window, then by stars band.
+    ///
+    /// Returns `None` if the partition can not be split any further.
+    pub fn split(&self, today: NaiveDate) -> Option<Vec<SearchPartition>> {
+        let (created_from, created_to) = self
+            .created
+            .unwrap_or((GITHUB_FIRST_REPOSITORY_DATE, today));
+        if created_from < created_to {
+            let created_middle =
+                created_from + Days::new(((created_to - created_from).num_days() / 2) as u64);
+            return Some(vec![
+                Self::new(Some((created_from, created_middle)), self.stars),
+                Self::new(
+                    Some((created_middle + Days::new(1), created_to)),
+                    self.stars,
+                ),
+            ]);
+        }
+
+        let (stars_from, stars_to) = self.stars.unwrap_or((0, None));
+        let stars_middle = match stars_to {
+            Some(stars_to) if stars_from < stars_to => stars_from + (stars_to - stars_from) / 2,
+            Some(_) => return None,
+            // The open-ended band is split at twice its lower bound, as the repositories get scarcer with the stars
+            None => stars_from.checked_mul(2)?.max(stars_from.checked_add(1)?),
+        };
+
+        Some(vec![
+            Self::new(self.created, Some((stars_from, Some(stars_middle)))),
+            Self::new(self.created, Some((stars_middle + 1, stars_to))),
+        ])
+    }
+}
+
+/// Parses the inclusive range of a qualifier value such as `10..20`, `10..*`, `>=10`, `<10` or `10`.
+///
+/// The open lower end of the range is replaced by the given minimum, the open upper end is left as `None`, and `None` is
+/// returned if the value can not be parsed or the range is empty.
+fn parse_qualifier_range<T: Copy + Ord + FromStr>(
+    value: &str,
+    min: T,
+    successor: impl Fn(T) -> Option<T>,
+    predecessor: impl Fn(T) -> Option<T>,
+) -> Option<(T, Option<T>)> {
+    let parse = |bound: &str| bound.parse::<T>().ok();
+    let (from, to) = if let Some((from, to)) = value.split_once("..") {
+        (
+            match from {
+                "*" => min,
+                from => parse(from)?,
+            },
+            match to {
+                "*" => None,
+                to => Some(parse(to)?),
+            },
         )
+    } else if let Some(from) = value.strip_prefix(">=") {
+        (parse(from)?, None)
+    } else if let Some(from) = value.strip_prefix('>') {
+        (successor(parse(from)?)?, None)
+    } else if let Some(to) = value.strip_prefix("<=") {
+        (min, Some(parse(to)?))
+    } else if let Some(to) = value.strip_prefix('<') {
+        (min, Some(predecessor(parse(to)?)?))
+    } else {
+        let bound = parse(value)?;
+        (bound, Some(bound))
+    };
+
+    match to {
+        Some(to) if from > to => None,
+        to => Some((from, to)),
     }
 }
 
 /// A repository from organization request being made to the GitHub API
-#[derive(Debug, Serialize, PartialEq, Eq, Clone, Hash)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Hash)]
 pub struct RepositoriesFromOrganizationRequest {
     /// The organization name.
     pub(crate) organization_name: String,
@@ -150,3 +436,325 @@ impl Display for RepositoriesFromOrganizationRequest {
         )
     }
 }
+
+/// How the repositories of the organizations found by a search are fetched.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrganizationTraversal {
+    /// Walk the repositories connection of the organization, covering all its public repositories.
+    #[default]
+    Connection,
+
+    /// Search the starred repositories of the organization, capped at 1,000 results.
+    Search,
+}
+
+/// A request walking the repositories connection of an organization on the GitHub API, ordered by stars
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Hash)]
+pub struct OrganizationRepositoriesRequest {
+    /// The organization name.
+    pub(crate) organization_name: String,
+
+    /// The number of repositories to return.
+    pub(crate) first: u16,
+
+    /// The cursor for pagination.
+    pub(crate) after: Option<String>,
+}
+
+impl OrganizationRepositoriesRequest {
+    /// Creates a new `Request` with the given organization name, first, and after values.
+    pub fn new(organization_name: &str, first: u16, after: Option<String>) -> Self {
+        Self {
+            organization_name: organization_name.to_string(),
+            first,
+            after,
+        }
+    }
+}
+
+impl Display for OrganizationRepositoriesRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "OrganizationRepositoriesRequest: organization_name={}, first={}, after={:?}",
+            self.organization_name, self.first, self.after
+        )
+    }
+}
+
+/// A repository from user request being made to the GitHub API
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Hash)]
+pub struct RepositoriesFromUserRequest {
+    /// The user name.
+    pub(crate) user_name: String,
+
+    /// The number of repositories to return.
+    pub(crate) first: u16,
+
+    /// The cursor for pagination.
+    pub(crate) after: Option<String>,
+}
+
+impl RepositoriesFromUserRequest {
+    /// Creates a new `Request` with the given user name, first, and after values.
+    pub fn new(user_name: &str, first: u16, after: Option<String>) -> Self {
+        Self {
+            user_name: user_name.to_string(),
+            first,
+            after,
+        }
+    }
+}
+
+impl Display for RepositoriesFromUserRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "RepositoriesFromUserRequest: user_name={}, first={}, after={:?}",
+            self.user_name, self.first, self.after
+        )
+    }
+}
+
+/// A request fetching again known repositories from the GitHub API by their node identifiers
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Hash)]
+pub struct RefreshRepositoriesRequest {
+    /// The node identifiers of the repositories.
+    pub(crate) node_ids: Vec<String>,
+}
+
+impl RefreshRepositoriesRequest {
+    /// Creates a new `Request` with the given node identifiers.
+    pub fn new(node_ids: Vec<String>) -> Self {
+        Self { node_ids }
+    }
+}
+
+impl Display for RefreshRepositoriesRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "RefreshRepositoriesRequest: node_ids=[{}]",
+            self.node_ids.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod request {
+        use super::*;
+
+        #[test]
+        fn serialization_round_trip() {
+            let requests = vec![
+                Request::SearchOrganization(
+                    SearchOrganizationRequest::new("is:public", 100, Some("cursor".to_string()))
+                        .with_partition(SearchPartition::new(
+                            Some((
+                                NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+                                NaiveDate::from_ymd_opt(2020, 12, 31).unwrap(),
+                            )),
+                            Some((10, Some(20))),
+                        )),
+                ),
+                Request::RepositoriesFromOrganization(RepositoriesFromOrganizationRequest::new(
+                    "org-1", 100, None,
+                )),
+                Request::RepositoriesFromUser(RepositoriesFromUserRequest::new(
+                    "user-1",
+                    100,
+                    Some("cursor".to_string()),
+                )),
+                Request::OrganizationRepositories(OrganizationRepositoriesRequest::new(
+                    "org-1",
+                    100,
+                    Some("cursor".to_string()),
+                )),
+            ];
+
+            let serialized = serde_json::to_string(&requests).unwrap();
+            let deserialized: Vec<Request> = serde_json::from_str(&serialized).unwrap();
+
+            assert_eq!(requests, deserialized);
+        }
+    }
+
+    mod search_partition {
+        use super::*;
+
+        fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+            NaiveDate::from_ymd_opt(year, month, day).unwrap()
+        }
+
+        #[test]
+        fn qualifiers_of_unbounded_partition() {
+            let partition = SearchPartition::default();
+
+            assert_eq!("", partition.qualifiers());
+        }
+
+        #[test]
+        fn qualifiers_of_bounded_partition() {
+            let partition = SearchPartition::new(
+                Some((date(2020, 1, 1), date(2020, 12, 31))),
+                Some((10, Some(20))),
+            );
+
+            assert_eq!(
+                " created:2020-01-01..2020-12-31 stars:10..20",
+                partition.qualifiers()
+            );
+        }
+
+        #[test]
+        fn qualifiers_of_open_ended_partition() {
+            let partition = SearchPartition::new(None, Some((101, None)));
+
+            assert_eq!(" stars:>=101", partition.qualifiers());
+        }
+
+        #[test]
+        fn split_unbounded_partition_by_creation_dates() {
+            let partition = SearchPartition::default();
+
+            let partitions = partition.split(date(2007, 10, 10)).unwrap();
+
+            assert_eq!(
+                vec![
+                    SearchPartition::new(Some((date(2007, 10, 1), date(2007, 10, 5))), None),
+                    SearchPartition::new(Some((date(2007, 10, 6), date(2007, 10, 10))), None),
+                ],
+                partitions
+            );
+        }
+
+        #[test]
+        fn split_single_day_partition_by_stars() {
+            let partition = SearchPartition::new(Some((date(2020, 1, 1), date(2020, 1, 1))), None);
+
+            let partitions = partition.split(date(2025, 1, 1)).unwrap();
+
+            assert_eq!(
+                vec![
+                    SearchPartition::new(
+                        Some((date(2020, 1, 1), date(2020, 1, 1))),
+                        Some((0, Some(1)))
+                    ),
+                    SearchPartition::new(
+                        Some((date(2020, 1, 1), date(2020, 1, 1))),
+                        Some((2, None))
+                    ),
+                ],
+                partitions
+            );
+        }
+
+        #[test]
+        fn split_open_ended_partition_at_twice_its_lower_bound() {
+            let partition = SearchPartition::new(
+                Some((date(2020, 1, 1), date(2020, 1, 1))),
+                Some((100, None)),
+            );
+
+            let partitions = partition.split(date(2025, 1, 1)).unwrap();
+
+            assert_eq!(
+                vec![
+                    SearchPartition::new(
+                        Some((date(2020, 1, 1), date(2020, 1, 1))),
+                        Some((100, Some(200)))
+                    ),
+                    SearchPartition::new(
+                        Some((date(2020, 1, 1), date(2020, 1, 1))),
+                        Some((201, None))
+                    ),
+                ],
+                partitions
+            );
+        }
+
+        #[test]
+        fn partition_from_query_qualifiers() {
+            let today = date(2025, 1, 1);
+            let partition = |query: &str| SearchPartition::try_from_query(query, today).unwrap();
+
+            assert_eq!(SearchPartition::default(), partition("is:public"));
+            assert_eq!(
+                SearchPartition::new(None, Some((101, None))),
+                partition("is:public stars:>100")
+            );
+            assert_eq!(
+                SearchPartition::new(None, Some((0, Some(99)))),
+                partition("stars:<100")
+            );
+            assert_eq!(
+                SearchPartition::new(
+                    Some((date(2020, 1, 1), date(2020, 12, 31))),
+                    Some((10, Some(20)))
+                ),
+                partition("created:2020-01-01..2020-12-31 stars:10..20")
+            );
+            assert_eq!(
+                SearchPartition::new(Some((date(2020, 1, 2), today)), Some((5, Some(5)))),
+                partition("created:>2020-01-01 stars:5")
+            );
+            assert_eq!(
+                SearchPartition::new(Some((date(2007, 10, 1), date(2020, 1, 1))), None),
+                partition("created:*..2020-01-01")
+            );
+        }
+
+        #[test]
+        fn partition_from_query_fails_on_unsupported_qualifiers() {
+            let today = date(2025, 1, 1);
+
+            assert!(SearchPartition::try_from_query("stars:>many", today).is_err());
+            assert!(SearchPartition::try_from_query("stars:20..10", today).is_err());
+            assert!(SearchPartition::try_from_query("created:>2025-01-01", today).is_err());
+            assert!(
+                SearchPartition::try_from_query("created:>2020-01-01T00:00:00Z", today).is_err()
+            );
+        }
+
+        #[test]
+        fn split_partition_within_query_qualifiers() {
+            let request = SearchOrganizationRequest::new("is:public stars:>100", 10, None);
+
+            let partitions = request
+                .split_partition(date(2007, 10, 10))
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(
+                vec![
+                    SearchPartition::new(
+                        Some((date(2007, 10, 1), date(2007, 10, 5))),
+                        Some((101, None))
+                    ),
+                    SearchPartition::new(
+                        Some((date(2007, 10, 6), date(2007, 10, 10))),
+                        Some((101, None))
+                    ),
+                ],
+                partitions
+            );
+            assert_eq!(
+                "is:public created:2007-10-01..2007-10-05 stars:>=101",
+                request.with_partition(partitions[0].clone()).search_query()
+            );
+        }
+
+        #[test]
+        fn split_fails_when_partition_is_atomic() {
+            let partition = SearchPartition::new(
+                Some((date(2020, 1, 1), date(2020, 1, 1))),
+                Some((10, Some(10))),
+            );
+
+            assert_eq!(None, partition.split(date(2025, 1, 1)));
+        }
+    }
+}
@@ -8,6 +8,12 @@ pub struct Response {
 
     /// The API rate limit information
     pub(crate) rate_limit: FetcherRateLimit,
+
+    /// The label of the API token used to fetch the response
+    pub(crate) api_token: Option<String>,
+
+    /// The node identifiers of the requested repositories that are deleted or inaccessible
+    pub(crate) unreachable_node_ids: Vec<String>,
 }
 
 impl Response {
@@ -16,9 +22,23 @@ impl Response {
         Self {
             repositories,
             rate_limit,
+            api_token: None,
+            unreachable_node_ids: Vec::new(),
         }
     }
 
+    /// Sets the label of the API token used to fetch the response.
+    pub fn with_api_token(mut self, api_token: &str) -> Self {
+        self.api_token = Some(api_token.to_string());
+        self
+    }
+
+    /// Sets the node identifiers of the requested repositories that are deleted or inaccessible.
+    pub fn with_unreachable_node_ids(mut self, unreachable_node_ids: Vec<String>) -> Self {
+        self.unreachable_node_ids = unreachable_node_ids;
+        self
+    }
+
     /// Retrieves the list of repositories.
     pub fn repositories(&self) -> &[Repository] {
         &self.repositories
@@ -28,4 +48,14 @@ impl Response {
     pub fn rate_limit(&self) -> &FetcherRateLimit {
         &self.rate_limit
     }
+
+    /// Retrieves the label of the API token used to fetch the response.
+    pub fn api_token(&self) -> Option<&str> {
+        self.api_token.as_deref()
+    }
+
+    /// Retrieves the node identifiers of the requested repositories that are deleted or inaccessible.
+    pub fn unreachable_node_ids(&self) -> &[String] {
+        &self.unreachable_node_ids
+    }
 }